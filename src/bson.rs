@@ -75,7 +75,7 @@ impl BsonType {
             BsonType::Document => BsonTag::Document,
             BsonType::Array => BsonTag::Array,
             BsonType::Binary => BsonTag::Binary,
-            BsonType::Guid => BsonTag::Boolean, // GUID is a kind of binary in bson
+            BsonType::Guid => BsonTag::Binary, // GUID is a kind of binary in bson
             BsonType::ObjectId => BsonTag::ObjectId,
             BsonType::Boolean => BsonTag::Boolean,
             BsonType::DateTime => BsonTag::DateTime,
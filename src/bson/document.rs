@@ -36,12 +36,10 @@ impl Document {
 
     /// Adds value to document.
     ///
-    /// ### Panics
-    /// This function will panic if the key contains null char (`'\0'`)
+    /// Keys containing null char (`'\0'`) cannot be written as bson, so writing the document
+    /// with such keys to the database returns [`ErrorKind::InvalidDocumentKey`](crate::ErrorKind::InvalidDocumentKey).
     pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) {
-        let key = key.into();
-        check_key(&key);
-        self.inner.insert(key.into(), value.into());
+        self.inner.insert(key.into().into(), value.into());
     }
 
     /// Gets the value with `key`, or None if not exists
@@ -87,10 +85,26 @@ impl Document {
     }
 
     pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
-        let key = key.into();
-        check_key(&key);
+        Entry::new(self.inner.entry(CaseInsensitiveString(key.into())))
+    }
+
+    /// Returns the key containing null char in this document or nested documents.
+    pub(crate) fn find_invalid_key(&self) -> Option<&str> {
+        fn find_in_value(value: &Value) -> Option<&str> {
+            match value {
+                Value::Document(document) => document.find_invalid_key(),
+                Value::Array(array) => array.iter().find_map(find_in_value),
+                _ => None,
+            }
+        }
 
-        Entry::new(self.inner.entry(CaseInsensitiveString(key)))
+        self.inner.iter().find_map(|(key, value)| {
+            if key.as_str().contains('\0') {
+                Some(key.as_str())
+            } else {
+                find_in_value(value)
+            }
+        })
     }
 }
 
@@ -301,14 +315,6 @@ impl<'a> VacantEntry<'a> {
     }
 }
 
-#[track_caller]
-fn check_key(key: &String) {
-    assert!(
-        !key.as_bytes().contains(&0),
-        "Key contains null char, which is disallowed for bson"
-    );
-}
-
 impl Debug for Document {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         Debug::fmt(&self.inner, f)
@@ -10,7 +10,11 @@ pub struct Guid {
 impl Guid {
     #[allow(clippy::new_without_default)]
     pub fn new() -> Guid {
-        let mut bytes = rand::random::<[u8; 16]>();
+        Self::from_random_bytes(rand::random())
+    }
+
+    /// Creates version 4 (random) GUID from the random bytes
+    pub(crate) fn from_random_bytes(mut bytes: [u8; 16]) -> Guid {
         bytes[6] = bytes[6] & 0x0F | 0x40;
         bytes[8] = bytes[8] & 0x3F | 0x80;
         Guid::from_bytes(bytes)
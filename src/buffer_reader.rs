@@ -92,6 +92,15 @@ impl<'a> BufferReader<'a> {
     pub fn position(&self) -> usize {
         self.global_position
     }
+
+    /// Returns the number of bytes not read yet
+    pub fn remaining(&self) -> usize {
+        self.slices[self.slice_index..]
+            .iter()
+            .map(|slice| slice.len())
+            .sum::<usize>()
+            - self.position_in_slice
+    }
 }
 
 #[allow(dead_code)]
@@ -141,6 +150,9 @@ impl BufferReader<'_> {
     pub fn read_cstring(&mut self) -> Option<String> {
         let mut bytes = Vec::new();
         loop {
+            if self.remaining() == 0 {
+                return None;
+            }
             let byte = self.read_u8();
             if byte == 0 {
                 break;
@@ -161,6 +173,9 @@ impl bson::BsonReader for BufferReader<'_> {
     type Error = bson::ParseError;
 
     fn read_fully(&mut self, bytes: &mut [u8]) -> std::result::Result<(), Self::Error> {
+        if self.remaining() < bytes.len() {
+            return Err(bson::ParseError::SizeExceeded);
+        }
         self.read_buffer(bytes);
         Ok(())
     }
@@ -16,50 +16,109 @@ mod tokenizer;
 
 /// The type represents expression parsing error
 #[derive(Debug)]
-pub struct ParseError(String);
-// TODO: distinguishable with enum
+pub struct ParseError {
+    kind: ParseErrorKind,
+    message: String,
+    position: Option<usize>,
+}
+
+/// The kind of [`ParseError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The token is not valid at the position.
+    UnexpectedToken,
+    /// The expression returns multiple values where single value is expected.
+    UnexpectedSequence,
+    /// The expression returns single value where multiple values are expected.
+    UnexpectedScalar,
+    /// The method or function is not found, or called with wrong number of arguments.
+    BadInvocation,
+    /// The expression is valid for LiteDB but not supported by this implementation.
+    Unsupported,
+}
 
 impl ParseError {
-    fn bad_invocation(f: &str) -> Self {
-        Self(format!("Bad invocation of {}", f))
+    fn new(kind: ParseErrorKind, message: String, position: Option<usize>) -> Self {
+        Self {
+            kind,
+            message,
+            position,
+        }
+    }
+
+    fn bad_invocation(f: &str, position: Option<usize>) -> Self {
+        Self::new(
+            ParseErrorKind::BadInvocation,
+            format!("Bad invocation of {}", f),
+            position,
+        )
     }
 
     #[inline]
     fn unexpected_sequence(position: std::fmt::Arguments) -> Self {
-        Self(format!(
-            "Scalar expression is expected, but sequence is provided at {position}"
-        ))
+        Self::new(
+            ParseErrorKind::UnexpectedSequence,
+            format!("Scalar expression is expected, but sequence is provided at {position}"),
+            None,
+        )
     }
 
     #[allow(dead_code)]
     #[inline]
     fn unexpected_scalar(position: std::fmt::Arguments) -> Self {
-        Self(format!(
-            "Sequence expression is expected, but scalar is provided at {position}"
-        ))
+        Self::new(
+            ParseErrorKind::UnexpectedScalar,
+            format!("Sequence expression is expected, but scalar is provided at {position}"),
+            None,
+        )
     }
 
     #[inline]
     fn unsupported(thing: std::fmt::Arguments) -> Self {
-        Self(format!("Unsupported expression: {}", thing))
+        Self::new(
+            ParseErrorKind::Unsupported,
+            format!("Unsupported expression: {}", thing),
+            None,
+        )
     }
 
     #[inline]
     fn unexpected_token(token: &Token, message: std::fmt::Arguments) -> Self {
-        if token.typ == TokenType::String {
-            Self(format!(r#"unexpected token: {message}: "{}""#, token.value))
+        let message = if token.typ == TokenType::String {
+            format!(r#"unexpected token: {message}: "{}""#, token.value)
         } else {
-            Self(format!(r#"unexpected token: {message}: {}"#, token.value))
-        }
+            format!(r#"unexpected token: {message}: {}"#, token.value)
+        };
+        Self::new(
+            ParseErrorKind::UnexpectedToken,
+            message,
+            Some(token.position),
+        )
+    }
+
+    pub fn kind(&self) -> ParseErrorKind {
+        self.kind
+    }
+
+    /// Returns the byte offset in the expression source where the error is found, if known.
+    pub fn position(&self) -> Option<usize> {
+        self.position
     }
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        f.write_str(&self.message)?;
+        if let Some(position) = self.position {
+            write!(f, " at {position}")?;
+        }
+        Ok(())
     }
 }
 
+impl std::error::Error for ParseError {}
+
 type Error = super::Error;
 
 #[derive(Debug, Eq, PartialEq, Copy, Clone)]
@@ -510,7 +569,6 @@ enum TokenType {
 struct Token<'a> {
     pub typ: TokenType,
     value: Cow<'a, str>,
-    #[allow(dead_code)]
     position: usize,
 }
 
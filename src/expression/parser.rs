@@ -1202,6 +1202,7 @@ fn try_parse_method_call(
     else {
         return Err(ParseError::bad_invocation(
             &token.value.to_ascii_uppercase(),
+            Some(token.position),
         ));
     };
 
@@ -1670,7 +1671,7 @@ fn parse_function(
     src.push(')');
 
     let Some(expression) = expr_gen(left.expression, closure, args) else {
-        return Err(ParseError::bad_invocation(function_name));
+        return Err(ParseError::bad_invocation(function_name, None));
     };
 
     Ok(Some(BsonExpression {
@@ -1,29 +1,66 @@
+mod disk;
+#[cfg(feature = "tokio")]
+mod disk_async;
+#[cfg(feature = "encryption")]
+mod encryption;
 mod index_helper;
+mod integrity;
+mod lazy;
+mod log_file;
 mod offsets;
 mod operations;
 mod page;
 mod parser;
 mod pragma;
+mod rebuild;
+mod recovery;
+mod shared;
+mod snapshot;
+mod stats;
+mod system_collections;
+mod transaction;
 mod writer;
 
 use crate::bson;
 use crate::expression::BsonExpression;
-use crate::utils::{ArenaKey, CaseInsensitiveString, KeyArena, Order as InternalOrder};
+use crate::utils::{
+    ArenaKey, CaseInsensitiveString, KeyArena, Order as InternalOrder, PageAddress, Randomizer,
+};
+pub use crate::utils::{Collation, CompareOptions};
 use indexmap::IndexMap;
-pub use operations::Order;
+pub use integrity::IntegrityIssue;
+pub use lazy::LazyLiteDBFile;
+pub use operations::{IndexInfo, Order, RenameCollectionResult};
 use pragma::EnginePragmas;
-use std::sync::OnceLock;
+pub use rebuild::{RebuildOptions, RebuildReport};
+pub use recovery::{CollectionRecovery, RecoveryReport};
+pub use shared::SharedLiteDB;
+pub use stats::{CollectionStats, DatabaseStats, IndexStats, PageStats};
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+pub use transaction::Transaction;
 
 use crate::file_io::index_helper::IndexHelper;
 pub(crate) use writer::get_key_length;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct LiteDBFile {
     collections: IndexMap<CaseInsensitiveString, Collection>,
     creation_time: bson::DateTime,
     pragmas: EnginePragmas,
     index_arena: KeyArena<IndexNode>,
     data: KeyArena<DbDocument>,
+    /// The pages of the file as they were loaded (or last written to log file)
+    loaded_pages: Arc<[u8]>,
+    /// The last transaction id used in the log file
+    last_transaction_id: u32,
+    /// The path this file is opened from or saved to
+    path: Option<PathBuf>,
+    keep_backup: bool,
+    /// The source of skip list levels, auto ids and encryption salts
+    random: Randomizer,
+    #[cfg(feature = "encryption")]
+    password: Option<String>,
 }
 
 impl Default for LiteDBFile {
@@ -40,8 +77,36 @@ impl LiteDBFile {
             pragmas: EnginePragmas::default(),
             index_arena: KeyArena::new(),
             data: KeyArena::new(),
+            loaded_pages: Arc::new([]),
+            last_transaction_id: 0,
+            path: None,
+            keep_backup: false,
+            random: Randomizer::default(),
+            #[cfg(feature = "encryption")]
+            password: None,
         }
     }
+
+    /// Returns the time this database is created, recorded in the header page.
+    pub fn creation_time(&self) -> bson::DateTime {
+        self.creation_time
+    }
+
+    pub fn set_creation_time(&mut self, creation_time: bson::DateTime) {
+        self.creation_time = creation_time;
+    }
+
+    /// Makes random values used by this database deterministic with `seed`.
+    ///
+    /// After this call, skip list levels of index nodes, auto ids ([`BsonAutoId::ObjectId`] and
+    /// [`BsonAutoId::Guid`]), and salts for encryption are drawn from the RNG seeded with `seed`.
+    /// Generated ObjectIds are filled with random bytes instead of the time and the process.
+    ///
+    /// With the same seed and [creation time](Self::set_creation_time), the same sequence of operations
+    /// produces byte-identical files. This is useful for golden-file tests or reproducible bug reports.
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random = Randomizer::seeded(seed);
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -54,11 +119,13 @@ pub enum BsonAutoId {
     Guid = 11,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Collection {
     indexes: IndexMap<String, CollectionIndex>,
     #[cfg(feature = "sequential-index")]
     last_id: Option<i64>,
+    /// The collection page in the loaded file. `None` if not written yet
+    page_id: Option<u32>,
 }
 
 impl Collection {
@@ -67,6 +134,7 @@ impl Collection {
             indexes: IndexMap::new(),
             #[cfg(feature = "sequential-index")]
             last_id: None,
+            page_id: None,
         };
 
         static EXPRESSION: OnceLock<BsonExpression> = OnceLock::new();
@@ -85,11 +153,10 @@ impl Collection {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct CollectionIndex {
     // same as CollectionIndex
     slot: u8,
-    #[allow(dead_code)] // legacy; reserved
     index_type: u8,
     name: String,
     expression: String,
@@ -101,11 +168,14 @@ struct CollectionIndex {
     tail: ArenaKey<IndexNode>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct DbDocument {
     data: bson::Document,
     // First node in this list must be _id PK index
     index_nodes: Vec<ArenaKey<IndexNode>>,
+    /// The first data block in the loaded file.
+    /// `None` if the document is not written yet or modified (dirty)
+    position: Option<PageAddress>,
 }
 
 impl DbDocument {
@@ -113,11 +183,12 @@ impl DbDocument {
         Self {
             data,
             index_nodes: Vec::new(),
+            position: None,
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct IndexNode {
     slot: u8,
     levels: u8,
@@ -125,6 +196,8 @@ struct IndexNode {
     data: Option<ArenaKey<DbDocument>>,
     prev: Vec<Option<ArenaKey<IndexNode>>>, // prev key in index skip list
     next: Vec<Option<ArenaKey<IndexNode>>>, // prev key in index skip list
+    /// The position of this node in the loaded file. `None` if not written yet
+    position: Option<PageAddress>,
 }
 
 impl IndexNode {
@@ -136,6 +209,7 @@ impl IndexNode {
             data: None,
             prev: vec![None; levels as usize],
             next: vec![None; levels as usize],
+            position: None,
         }
     }
 
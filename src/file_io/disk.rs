@@ -0,0 +1,236 @@
+//! Reading and writing database files on disk.
+
+use super::*;
+use crate::constants::PAGE_SIZE;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+
+impl LiteDBFile {
+    /// Opens the database file at `path`.
+    ///
+    /// If there is the log file (`-log.db` file) next to the database file,
+    /// confirmed transactions in the log file are applied.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        let data = std::fs::read(path)?;
+        let mut file = match read_if_exists(&log_file_path(path))? {
+            Some(log) => LiteDBFile::parse_with_log(&data, &log)?,
+            None => LiteDBFile::parse(&data)?,
+        };
+        file.path = Some(path.to_owned());
+
+        Ok(file)
+    }
+
+    /// Saves the database to `path`, and `path` becomes the file of this database.
+    ///
+    /// The file is replaced atomically, and the log file is removed since it's merged to the new file.
+    /// Before replacing, the log file is applied to the current data file like checkpoint of LiteDB
+    /// so that no committed transaction is lost even if the process crashed while saving.
+    pub fn save(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let written = self.write_to(path)?;
+        self.saved(written);
+        self.path = Some(path.to_owned());
+        Ok(())
+    }
+
+    /// Saves a copy of the database to `path` in the same way as [`save`].
+    ///
+    /// Unlike [`save`], this database is still bound to the file it's opened from or last saved to,
+    /// so [`serialize_log`] keeps creating the log for that file.
+    ///
+    /// [`save`]: LiteDBFile::save
+    /// [`serialize_log`]: LiteDBFile::serialize_log
+    pub fn save_as(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to(path.as_ref())?;
+        Ok(())
+    }
+
+    /// Sets whether to keep the previous version of the file as `.bak` file on save.
+    pub fn set_keep_backup(&mut self, keep_backup: bool) {
+        self.keep_backup = keep_backup;
+    }
+
+    /// Writes the database to `path` and returns the written pages not committed yet
+    pub(super) fn write_to(&self, path: &Path) -> io::Result<writer::Written> {
+        let written = writer::write(self);
+
+        let encrypted = self.encrypted_content(&written.pages);
+
+        write_atomic(
+            path,
+            encrypted.as_deref().unwrap_or(&written.pages),
+            self.keep_backup,
+            self.password(),
+        )?;
+
+        Ok(written)
+    }
+
+    /// Returns the password used to read and write the data file on disk
+    pub(super) fn password(&self) -> Option<&str> {
+        #[cfg(feature = "encryption")]
+        return self.password.as_deref();
+        #[cfg(not(feature = "encryption"))]
+        None
+    }
+
+    /// Updates the state after the pages are written to the disk
+    pub(super) fn saved(&mut self, written: writer::Written) {
+        self.loaded_pages = written.commit(self).into();
+        self.last_transaction_id = 0;
+    }
+}
+
+pub(super) fn read_if_exists(path: &Path) -> io::Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(data) => Ok(Some(data)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Returns the path with suffix appended to the file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut file_name = path.file_name().map(OsString::from).unwrap_or_default();
+    file_name.push(suffix);
+    path.with_file_name(file_name)
+}
+
+/// Returns the path to the log file for the database file like `FileHelper.GetLogFile` in LiteDB.
+///
+/// For `path/to/vcc.liteDb`, this returns `path/to/vcc-log.liteDb`
+pub(super) fn log_file_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_stem().map(OsString::from).unwrap_or_default();
+    file_name.push("-log");
+    if let Some(extension) = path.extension() {
+        file_name.push(".");
+        file_name.push(extension);
+    }
+    path.with_file_name(file_name)
+}
+
+/// Returns the unique temporary file path for the database file.
+///
+/// The process id and a counter are included so that concurrent saves do not clobber each other.
+fn temp_file_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicU32 = AtomicU32::new(0);
+    let counter = COUNTER.fetch_add(1, Ordering::Relaxed);
+    with_suffix(path, &format!(".{}-{counter}.tmp", std::process::id()))
+}
+
+fn backup_file_path(path: &Path) -> PathBuf {
+    with_suffix(path, ".bak")
+}
+
+/// Writes `data` to `path` with sibling temporary file and atomic rename.
+fn write_atomic(
+    path: &Path,
+    data: &[u8],
+    keep_backup: bool,
+    password: Option<&str>,
+) -> io::Result<()> {
+    debug_assert!(data.len().is_multiple_of(PAGE_SIZE));
+
+    // The log file must be merged to the old data file before replacing the data file.
+    // If we removed the log file without merging, transactions only in the log would be lost
+    // when we crashed before rename, and if we kept the log file,
+    // the old log would be applied to the new file when we crashed after rename.
+    checkpoint(path, password)?;
+
+    if keep_backup && path.exists() {
+        let backup_path = backup_file_path(path);
+        std::fs::copy(path, &backup_path)?;
+        File::open(&backup_path)?.sync_all()?;
+    }
+
+    replace_file(path, data)
+}
+
+/// Replaces the file at `path` with `data` by writing to a sibling temporary file and renaming it.
+///
+/// The existing file is never modified, and the permissions of the existing file are kept.
+fn replace_file(path: &Path, data: &[u8]) -> io::Result<()> {
+    let temp_path = temp_file_path(path);
+
+    let result = (|| {
+        let mut temp = File::create(&temp_path)?;
+        temp.write_all(data)?;
+        match std::fs::metadata(path) {
+            Ok(metadata) => temp.set_permissions(metadata.permissions())?,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+        temp.sync_all()?;
+        drop(temp);
+
+        std::fs::rename(&temp_path, path)?;
+
+        sync_parent_dir(path)
+    })();
+
+    if result.is_err() {
+        std::fs::remove_file(&temp_path).ok();
+    }
+
+    result
+}
+
+/// Applies confirmed pages in the log file to the data file and removes the log file,
+/// like checkpoint of LiteDB.
+///
+/// The data file is replaced atomically with the log applied, and applying the log is idempotent,
+/// so if we crashed before removing the log file, the log file will be applied again on next open.
+fn checkpoint(path: &Path, password: Option<&str>) -> io::Result<()> {
+    let log_path = log_file_path(path);
+    let Some(log) = read_if_exists(&log_path)? else {
+        return Ok(());
+    };
+
+    if let Some(data) = read_if_exists(path)? {
+        replace_file(path, &checkpoint_image(&data, &log, password)?)?;
+    }
+
+    std::fs::remove_file(&log_path)?;
+    sync_parent_dir(path)
+}
+
+/// Returns the data file content with the log applied
+fn checkpoint_image(data: &[u8], log: &[u8], password: Option<&str>) -> io::Result<Vec<u8>> {
+    #[cfg(feature = "encryption")]
+    if super::encryption::is_encrypted_file(data) {
+        let Some(password) = password else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "the password is required to merge the log file of encrypted database",
+            ));
+        };
+        return Ok(super::encryption::apply_log(data, log, password)?);
+    }
+
+    let _ = password;
+    Ok(log_file::apply_log(data, log)?)
+}
+
+fn sync_parent_dir(path: &Path) -> io::Result<()> {
+    // On windows, directories cannot be opened as file so we cannot sync them
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        let parent = if parent.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            parent
+        };
+        File::open(parent)?.sync_all()?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
@@ -0,0 +1,53 @@
+//! Async version of [`disk`](super::disk) based on tokio.
+
+use super::*;
+use std::io;
+use std::path::Path;
+
+impl LiteDBFile {
+    /// Async version of [`open`](LiteDBFile::open).
+    ///
+    /// The file is read and parsed on the blocking thread pool.
+    pub async fn open_async(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_owned();
+        spawn_blocking(move || LiteDBFile::open(path)).await?
+    }
+
+    /// Async version of [`save`](LiteDBFile::save).
+    ///
+    /// Serialization and writing run on the blocking thread pool with a snapshot of this database
+    /// so that other tasks can run while serializing, on any runtime flavor.
+    pub async fn save_async(&mut self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        let written = self.write_to_async(path).await?;
+        self.saved(written);
+        self.path = Some(path.to_owned());
+        Ok(())
+    }
+
+    /// Async version of [`save_as`](LiteDBFile::save_as).
+    pub async fn save_as_async(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        self.write_to_async(path.as_ref()).await?;
+        Ok(())
+    }
+
+    async fn write_to_async(&self, path: &Path) -> io::Result<writer::Written> {
+        // the clone is cheap since the arenas are shared with copy-on-write
+        let file = self.clone();
+        let path = path.to_owned();
+        spawn_blocking(move || file.write_to(&path)).await?
+    }
+}
+
+/// Runs `f` on the blocking thread pool of the current tokio runtime.
+///
+/// Returns an error instead of panicking if this is not called within tokio runtime.
+async fn spawn_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> io::Result<T> {
+    let handle = tokio::runtime::Handle::try_current().map_err(io::Error::other)?;
+    handle
+        .spawn_blocking(f)
+        .await
+        .map_err(|_| io::Error::other("background task failed"))
+}
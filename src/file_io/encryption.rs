@@ -0,0 +1,165 @@
+//! Password protected (AES encrypted) files.
+//!
+//! This module implements the same format as `AesStream` in LiteDB.
+//!
+//! The encrypted file has one additional page (hidden page) at the beginning of the file.
+//! The hidden page has the encryption marker (`1`) at byte 0, salt at bytes 1-16,
+//! and 32 bytes of `1` encrypted at bytes 32-63 for checking password.
+//!
+//! Other pages are encrypted with AES-256 in ECB mode without padding.
+//! The key is derived from the password with PBKDF2-HMAC-SHA1 (`Rfc2898DeriveBytes` in C#).
+
+use super::disk::{log_file_path, read_if_exists};
+use super::*;
+use crate::constants::PAGE_SIZE;
+use crate::{ParseError, ParseResult};
+use aes::Aes256;
+use aes::cipher::{BlockDecrypt, BlockEncrypt, KeyInit, generic_array::GenericArray};
+use std::io;
+use std::path::Path;
+
+const ENCRYPTION_SALT_SIZE: usize = 16;
+const P_ENCRYPTED: usize = 0;
+const P_SALT: usize = 1;
+const P_PASSWORD_CHECK: usize = 32;
+const PASSWORD_CHECK_SIZE: usize = 32;
+/// The iteration count of `Rfc2898DeriveBytes` if not specified
+const KEY_DERIVE_ITERATIONS: u32 = 1000;
+const AES_BLOCK_SIZE: usize = 16;
+
+impl LiteDBFile {
+    /// Parses the password protected database file.
+    pub fn parse_encrypted(data: &[u8], password: &str) -> ParseResult<Self> {
+        let mut parsed = parser::parse(&decrypt(data, password)?)?;
+        parsed.password = Some(password.to_owned());
+        Ok(parsed)
+    }
+
+    /// Opens the password protected database file at `path`.
+    ///
+    /// Like [`open`](LiteDBFile::open), confirmed transactions in the log file are applied.
+    pub fn open_encrypted(path: impl AsRef<Path>, password: &str) -> io::Result<Self> {
+        let path = path.as_ref();
+
+        let data = decrypt(&std::fs::read(path)?, password)?;
+        let mut file = match read_if_exists(&log_file_path(path))? {
+            Some(log) => log_file::parse_with_log(&data, &decrypt_log(&log, password)?)?,
+            None => parser::parse(&data)?,
+        };
+        file.password = Some(password.to_owned());
+        file.path = Some(path.to_owned());
+
+        Ok(file)
+    }
+
+    /// Sets the password for the database file.
+    ///
+    /// If password is set, [`serialize`] and [`save`] will write encrypted file.
+    /// Passing `None` makes the file unencrypted.
+    ///
+    /// [`serialize`]: LiteDBFile::serialize
+    /// [`save`]: LiteDBFile::save
+    pub fn set_password(&mut self, password: Option<&str>) {
+        self.password = password.map(str::to_owned);
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.password.is_some()
+    }
+}
+
+/// Applies the log file to the encrypted data file, and returns the data file encrypted with the same salt
+pub(super) fn apply_log(data: &[u8], log: &[u8], password: &str) -> ParseResult<Vec<u8>> {
+    let pages = log_file::apply_log(&decrypt(data, password)?, &decrypt_log(log, password)?)?;
+
+    let salt = data[P_SALT..][..ENCRYPTION_SALT_SIZE].try_into().unwrap();
+    Ok(encrypt(&pages, password, salt))
+}
+
+/// Returns true if the file starts with encryption marker
+pub(super) fn is_encrypted_file(data: &[u8]) -> bool {
+    data.len() >= PAGE_SIZE && data[P_ENCRYPTED] == 1
+}
+
+fn create_cipher(password: &str, salt: &[u8]) -> Aes256 {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha1::Sha1>(password.as_bytes(), salt, KEY_DERIVE_ITERATIONS, &mut key);
+    Aes256::new(GenericArray::from_slice(&key))
+}
+
+/// Decrypts the file and returns the pages without hidden page
+pub(super) fn decrypt(data: &[u8], password: &str) -> ParseResult<Vec<u8>> {
+    // if the length is not multiple of PAGE_SIZE, crop
+    let data = &data[..(data.len() & !(PAGE_SIZE - 1))];
+
+    if !is_encrypted_file(data) {
+        return Err(ParseError::invalid_database());
+    }
+
+    let salt = &data[P_SALT..][..ENCRYPTION_SALT_SIZE];
+    let cipher = create_cipher(password, salt);
+
+    let mut check = data[P_PASSWORD_CHECK..][..PASSWORD_CHECK_SIZE].to_vec();
+    decrypt_blocks(&cipher, &mut check);
+    if !check.iter().all(|&x| x == 1) {
+        return Err(ParseError::invalid_password());
+    }
+
+    let mut pages = data[PAGE_SIZE..].to_vec();
+
+    for page in pages.chunks_mut(PAGE_SIZE) {
+        // blank pages are not encrypted. LiteDB returns blank page for them
+        if page[..AES_BLOCK_SIZE].iter().all(|&x| x == 0) {
+            page.fill(0);
+        } else {
+            decrypt_blocks(&cipher, page);
+        }
+    }
+
+    Ok(pages)
+}
+
+/// Decrypts the log file of the encrypted database.
+///
+/// LiteDB creates the log file with hidden page only when the log is first used,
+/// so the log file without any page is treated as empty log.
+/// Other log files without encryption marker are error since we cannot apply them.
+fn decrypt_log(log: &[u8], password: &str) -> ParseResult<Vec<u8>> {
+    if log.len() < PAGE_SIZE {
+        return Ok(Vec::new());
+    }
+
+    decrypt(log, password)
+}
+
+/// Encrypts the pages and prepends the hidden page
+pub(super) fn encrypt(pages: &[u8], password: &str, salt: [u8; ENCRYPTION_SALT_SIZE]) -> Vec<u8> {
+    let cipher = create_cipher(password, &salt);
+
+    let mut result = vec![0u8; PAGE_SIZE + pages.len()];
+
+    let (hidden, encrypted) = result.split_at_mut(PAGE_SIZE);
+
+    hidden[P_ENCRYPTED] = 1;
+    hidden[P_SALT..][..ENCRYPTION_SALT_SIZE].copy_from_slice(&salt);
+    let check = &mut hidden[P_PASSWORD_CHECK..][..PASSWORD_CHECK_SIZE];
+    check.fill(1);
+    encrypt_blocks(&cipher, check);
+
+    encrypted.copy_from_slice(pages);
+    encrypt_blocks(&cipher, encrypted);
+
+    result
+}
+
+fn encrypt_blocks(cipher: &Aes256, data: &mut [u8]) {
+    for block in data.chunks_exact_mut(AES_BLOCK_SIZE) {
+        cipher.encrypt_block(GenericArray::from_mut_slice(block));
+    }
+}
+
+fn decrypt_blocks(cipher: &Aes256, data: &mut [u8]) {
+    for block in data.chunks_exact_mut(AES_BLOCK_SIZE) {
+        cipher.decrypt_block(GenericArray::from_mut_slice(block));
+    }
+}
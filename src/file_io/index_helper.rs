@@ -1,10 +1,8 @@
 use crate::constants::{MAX_INDEX_KEY_LENGTH, MAX_LEVEL_LENGTH};
 use crate::expression::BsonExpression;
 use crate::file_io::{Collection, CollectionIndex, DbDocument, IndexNode, get_key_length};
-use crate::utils::{ArenaKey, Collation, KeyArena, Order};
+use crate::utils::{ArenaKey, Collation, KeyArena, Order, Randomizer};
 use crate::{Error, bson};
-use std::collections::HashSet;
-use std::hash::{BuildHasher, RandomState};
 
 pub(crate) struct IndexHelper;
 
@@ -59,6 +57,7 @@ impl IndexHelper {
     pub fn add_node(
         arena: &mut KeyArena<IndexNode>,
         data_arena: &mut KeyArena<DbDocument>,
+        random: &mut Randomizer,
         collation: &Collation,
         index: &CollectionIndex,
         key: bson::Value,
@@ -72,7 +71,7 @@ impl IndexHelper {
             return Err(Error::invalid_index_key_type());
         }
 
-        let levels = Self::flip();
+        let levels = Self::flip(random);
 
         Self::add_node_with_levels(arena, data_arena, collation, index, key, data_block, levels)
     }
@@ -89,7 +88,8 @@ impl IndexHelper {
         let key_length = get_key_length(&key);
 
         if key_length > MAX_INDEX_KEY_LENGTH {
-            return Err(Error::index_key_too_long());
+            let id = data_arena[data_block].data.get("_id");
+            return Err(Error::index_key_too_long(&index.name, id));
         }
 
         let node_key = arena.alloc(IndexNode::new(index.slot, insert_levels, key));
@@ -112,10 +112,11 @@ impl IndexHelper {
                 let diff = collation.compare(&arena[right_key].key, &arena[node_key].key);
 
                 if diff.is_eq() && index.unique {
-                    return Err(Error::index_duplicate_key(
-                        &index.name,
-                        arena[node_key].key.clone(),
-                    ));
+                    // unlink the node from the upper levels already linked
+                    let node = arena.free(node_key);
+                    Self::delete_single_node(arena, &node);
+
+                    return Err(Error::index_duplicate_key(&index.name, node.key));
                 }
 
                 if diff.is_gt() {
@@ -154,11 +155,11 @@ impl IndexHelper {
         Ok(node_key)
     }
 
-    fn flip() -> u8 {
+    fn flip(random: &mut Randomizer) -> u8 {
         let mut levels = 1;
 
         //for (int R = Randomizer.Next(); (R & 1) == 1; R >>= 1)
-        let mut random = (RandomState::new().hash_one(0) & 0xFFFFFFFF) as u32;
+        let mut random = random.next_u32();
         while (random & 1) == 1 {
             levels += 1;
             if levels == MAX_LEVEL_LENGTH {
@@ -182,26 +183,34 @@ impl IndexHelper {
     pub fn delete_all(arena: &mut KeyArena<IndexNode>, index_nodes: &[ArenaKey<IndexNode>]) {
         for &current_key in index_nodes {
             let node = arena.free(current_key);
-            Self::delete_single_node(arena, node);
+            Self::delete_single_node(arena, &node);
         }
     }
 
-    pub fn delete_list(
-        arena: &mut KeyArena<IndexNode>,
-        index_nodes: &mut Vec<ArenaKey<IndexNode>>,
-        to_delete: HashSet<ArenaKey<IndexNode>>,
-    ) {
-        index_nodes.retain(|&current_key| {
-            let retain = !to_delete.contains(&current_key);
-            if !retain {
-                let node = arena.free(current_key);
-                Self::delete_single_node(arena, node);
+    /// Unlinks the node from the skip list without freeing it.
+    ///
+    /// The node keeps its links so that it can be linked again with [`Self::relink_node`].
+    pub fn unlink_node(arena: &mut KeyArena<IndexNode>, node_key: ArenaKey<IndexNode>) {
+        let node = arena[node_key].clone();
+        Self::delete_single_node(arena, &node);
+    }
+
+    /// Links the node unlinked with [`Self::unlink_node`] again.
+    ///
+    /// If multiple nodes are unlinked, they must be linked in the reverse order of unlinking.
+    pub fn relink_node(arena: &mut KeyArena<IndexNode>, node_key: ArenaKey<IndexNode>) {
+        for level in 0..arena[node_key].levels as usize {
+            if let Some(prev) = arena[node_key].prev[level] {
+                arena[prev].next[level] = Some(node_key);
+            }
+
+            if let Some(next) = arena[node_key].next[level] {
+                arena[next].prev[level] = Some(node_key);
             }
-            retain
-        });
+        }
     }
 
-    fn delete_single_node(arena: &mut KeyArena<IndexNode>, node: IndexNode) {
+    fn delete_single_node(arena: &mut KeyArena<IndexNode>, node: &IndexNode) {
         for level in (0..node.levels).rev() {
             // get previous and next nodes (between my deleted node)
 
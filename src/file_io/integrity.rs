@@ -0,0 +1,380 @@
+//! Checking the consistency of the database.
+//!
+//! Unlike the parser, which stops at the first problem, the checker collects all problems found
+//! as [`IntegrityIssue`]s.
+
+use super::page::{PageBuffer, PageType};
+use super::parser::collection_page::RawCollectionPage;
+use super::parser::header_page::HeaderPage;
+use super::*;
+use crate::ParseError;
+use crate::constants::{MAX_LEVEL_LENGTH, PAGE_SIZE};
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+
+/// The problem found by [`LiteDBFile::check_integrity`] or [`LiteDBFile::check_file_integrity`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IntegrityIssue {
+    /// The file cannot be parsed.
+    InvalidFile(ParseError),
+    /// The keys in the index skip list are not in ascending order under the collation.
+    UnorderedIndex {
+        collection: String,
+        index: String,
+        level: u8,
+    },
+    /// The prev / next links of the index skip list are not symmetric,
+    /// or the list does not reach the tail node.
+    InconsistentLink {
+        collection: String,
+        index: String,
+        level: u8,
+    },
+    /// The unique index has the same key more than once.
+    DuplicateKey {
+        collection: String,
+        index: String,
+        key: bson::Value,
+    },
+    /// The document is referenced from zero or multiple nodes of the `_id` index.
+    PrimaryKeyNodeCount {
+        collection: String,
+        id: bson::Value,
+        count: usize,
+    },
+    /// The index nodes of the document do not match the nodes in indexes pointing to the document.
+    IndexNodesMismatch { collection: String, id: bson::Value },
+    /// The document is not referenced from any index of any collection.
+    OrphanedDocument { id: bson::Value },
+    /// The free page list has a cycle or a page outside of the file.
+    BrokenFreeList { first_page: u32 },
+    /// The page in the free page list has the wrong page type, collection, or page list slot.
+    InvalidFreeListPage { first_page: u32, page_id: u32 },
+}
+
+impl Display for IntegrityIssue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntegrityIssue::InvalidFile(e) => write!(f, "invalid file: {e}"),
+            IntegrityIssue::UnorderedIndex {
+                collection,
+                index,
+                level,
+            } => write!(
+                f,
+                "index `{index}` of `{collection}` is not ordered at level {level}"
+            ),
+            IntegrityIssue::InconsistentLink {
+                collection,
+                index,
+                level,
+            } => write!(
+                f,
+                "index `{index}` of `{collection}` has inconsistent links at level {level}"
+            ),
+            IntegrityIssue::DuplicateKey {
+                collection,
+                index,
+                key,
+            } => write!(
+                f,
+                "unique index `{index}` of `{collection}` has duplicated key {key:?}"
+            ),
+            IntegrityIssue::PrimaryKeyNodeCount {
+                collection,
+                id,
+                count,
+            } => write!(
+                f,
+                "document {id:?} in `{collection}` has {count} primary key nodes"
+            ),
+            IntegrityIssue::IndexNodesMismatch { collection, id } => write!(
+                f,
+                "index nodes of document {id:?} in `{collection}` do not match indexes"
+            ),
+            IntegrityIssue::OrphanedDocument { id } => {
+                write!(f, "document {id:?} is not referenced from any index")
+            }
+            IntegrityIssue::BrokenFreeList { first_page } => {
+                write!(f, "free page list starting at {first_page} is broken")
+            }
+            IntegrityIssue::InvalidFreeListPage {
+                first_page,
+                page_id,
+            } => write!(
+                f,
+                "page {page_id} in free page list starting at {first_page} is not a valid free page"
+            ),
+        }
+    }
+}
+
+impl LiteDBFile {
+    /// Checks the consistency of the indexes and documents in this database.
+    ///
+    /// Returns an empty `Vec` if no problem is found.
+    pub fn check_integrity(&self) -> Vec<IntegrityIssue> {
+        let mut issues = Vec::new();
+        let mut referenced = HashSet::new();
+
+        for (name, collection) in &self.collections {
+            check_collection(self, &name.0, collection, &mut referenced, &mut issues);
+        }
+
+        // documents removed from all indexes but left in the data
+        for (data, document) in self.data.iter() {
+            if !referenced.contains(&data) {
+                issues.push(IntegrityIssue::OrphanedDocument {
+                    id: document_id(document),
+                });
+            }
+        }
+
+        issues
+    }
+
+    /// Checks the consistency of the database file.
+    ///
+    /// In addition to [`check_integrity`](LiteDBFile::check_integrity),
+    /// this checks the structures only exist in the file like free page lists.
+    pub fn check_file_integrity(data: &[u8]) -> Vec<IntegrityIssue> {
+        let mut issues = Vec::new();
+
+        let file = match parser::parse(data) {
+            Ok(file) => file,
+            Err(e) => {
+                issues.push(IntegrityIssue::InvalidFile(e));
+                return issues;
+            }
+        };
+
+        check_free_lists(data, &mut issues);
+        issues.extend(file.check_integrity());
+
+        issues
+    }
+}
+
+fn check_collection(
+    file: &LiteDBFile,
+    name: &str,
+    collection: &Collection,
+    referenced: &mut HashSet<ArenaKey<DbDocument>>,
+    issues: &mut Vec<IntegrityIssue>,
+) {
+    let arena = &file.index_arena;
+    let collation = &file.pragmas.collation;
+    let collation_supported = collation.is_supported();
+
+    // the nodes pointing to each document
+    let mut document_nodes = IndexMap::<ArenaKey<DbDocument>, Vec<ArenaKey<IndexNode>>>::new();
+
+    for index in collection.indexes.values() {
+        for level in 0..MAX_LEVEL_LENGTH {
+            let inconsistent = || IntegrityIssue::InconsistentLink {
+                collection: name.to_string(),
+                index: index.name.clone(),
+                level,
+            };
+
+            let mut cur = index.head;
+            let mut counter = 0;
+            while cur != index.tail {
+                let next = arena[cur].next.get(level as usize).copied().flatten();
+                if next.is_none() && level != 0 {
+                    // upper levels may end without tail node like LiteDB does
+                    break;
+                }
+                let Some(next_node) = next.and_then(|next| arena.get(next)) else {
+                    issues.push(inconsistent());
+                    break;
+                };
+                let next = next.unwrap();
+
+                counter += 1;
+                if counter > arena.len()
+                    || next_node.prev.get(level as usize) != Some(&Some(cur))
+                    || next_node.levels <= level
+                {
+                    issues.push(inconsistent());
+                    break;
+                }
+
+                let order = collation.compare(&arena[cur].key, &next_node.key);
+                // we cannot check the order of strings under unsupported collation
+                let comparable = collation_supported
+                    || !matches!(
+                        (&arena[cur].key, &next_node.key),
+                        (bson::Value::String(_), bson::Value::String(_))
+                    );
+                if comparable && order.is_gt() {
+                    issues.push(IntegrityIssue::UnorderedIndex {
+                        collection: name.to_string(),
+                        index: index.name.clone(),
+                        level,
+                    });
+                }
+                if level == 0 && index.unique && order.is_eq() && cur != index.head {
+                    issues.push(IntegrityIssue::DuplicateKey {
+                        collection: name.to_string(),
+                        index: index.name.clone(),
+                        key: next_node.key.clone(),
+                    });
+                }
+
+                if level == 0 && next != index.tail {
+                    match next_node.data {
+                        Some(data) if file.data.get(data).is_some() => {
+                            document_nodes.entry(data).or_default().push(next);
+                        }
+                        _ => issues.push(inconsistent()),
+                    }
+                }
+
+                cur = next;
+            }
+        }
+    }
+
+    let pk_slot = collection.pk_index().slot;
+
+    for (&data, nodes) in &document_nodes {
+        referenced.insert(data);
+        let document = &file.data[data];
+        let id = || document_id(document);
+
+        let pk_nodes = nodes.iter().filter(|&&x| arena[x].slot == pk_slot).count();
+        if pk_nodes != 1 {
+            issues.push(IntegrityIssue::PrimaryKeyNodeCount {
+                collection: name.to_string(),
+                id: id(),
+                count: pk_nodes,
+            });
+        }
+
+        let expected = nodes.iter().copied().collect::<HashSet<_>>();
+        let actual = document.index_nodes.iter().copied().collect::<HashSet<_>>();
+        let first_is_pk = document
+            .index_nodes
+            .first()
+            .and_then(|&x| arena.get(x))
+            .is_some_and(|x| x.slot == pk_slot);
+        if expected != actual || actual.len() != document.index_nodes.len() || !first_is_pk {
+            issues.push(IntegrityIssue::IndexNodesMismatch {
+                collection: name.to_string(),
+                id: id(),
+            });
+        }
+    }
+}
+
+fn document_id(document: &DbDocument) -> bson::Value {
+    document
+        .data
+        .try_get("_id")
+        .cloned()
+        .unwrap_or(bson::Value::Null)
+}
+
+/// The pages expected in the free page list
+struct FreeList {
+    first_page: u32,
+    page_type: PageType,
+    /// The collection page id for data / index pages
+    collection: Option<u32>,
+    /// The page list slot for data / index pages
+    slot: Option<u8>,
+}
+
+fn check_free_lists(data: &[u8], issues: &mut Vec<IntegrityIssue>) {
+    let data = &data[..(data.len() & !(PAGE_SIZE - 1))];
+    let pages = data
+        .chunks(PAGE_SIZE)
+        .map(PageBuffer::new)
+        .collect::<Vec<_>>();
+
+    // an empty file can be parsed as empty database
+    let Some(&header_page) = pages.first() else {
+        return;
+    };
+    // parse succeeded so the header is valid
+    let Ok(header) = HeaderPage::parse(header_page) else {
+        return;
+    };
+
+    let mut lists = vec![FreeList {
+        first_page: header.free_empty_page_list,
+        page_type: PageType::Empty,
+        collection: None,
+        slot: None,
+    }];
+
+    for &page in &pages {
+        if page.page_type() != Some(PageType::Collection) {
+            continue;
+        }
+        let Ok(collection) = RawCollectionPage::parse(page) else {
+            continue;
+        };
+        let collection_page = page.page_id();
+        lists.extend((collection.free_data_page_list.iter().enumerate()).map(
+            |(slot, &first_page)| FreeList {
+                first_page,
+                page_type: PageType::Data,
+                collection: Some(collection_page),
+                slot: Some(slot as u8),
+            },
+        ));
+        // index pages with free space has slot 0, and full pages has slot 1
+        lists.extend(collection.indexes.values().map(|index| FreeList {
+            first_page: index.free_index_page_list,
+            page_type: PageType::Index,
+            collection: Some(collection_page),
+            slot: Some(0),
+        }));
+    }
+
+    for list in lists {
+        let first_page = list.first_page;
+        let mut visited = HashSet::new();
+        let mut cur = first_page;
+        while cur != u32::MAX {
+            let Some(&page) = pages.get(cur as usize) else {
+                issues.push(IntegrityIssue::BrokenFreeList { first_page });
+                break;
+            };
+            if !visited.insert(cur) {
+                issues.push(IntegrityIssue::BrokenFreeList { first_page });
+                break;
+            }
+            if page.page_type() != Some(list.page_type)
+                || list.collection.is_some_and(|x| x != page.col_id())
+                || list.slot.is_some_and(|x| x != page.page_list_slot())
+            {
+                issues.push(IntegrityIssue::InvalidFreeListPage {
+                    first_page,
+                    page_id: cur,
+                });
+            }
+            cur = page.next_page_id();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orphaned_document() {
+        let mut file = LiteDBFile::new();
+        file.data.alloc(DbDocument::new(document! {"_id" => 1}));
+
+        let issues = file.check_integrity();
+        assert!(
+            matches!(&issues[..], [IntegrityIssue::OrphanedDocument { id }] if id == &bson::Value::from(1)),
+            "{issues:?}"
+        );
+    }
+}
@@ -0,0 +1,545 @@
+//! Read-only access to the database file without loading entire file.
+//!
+//! [`LiteDBFile::parse`] loads all documents and index nodes to memory at once.
+//! [`LazyLiteDBFile`] instead keeps the reader and loads pages on demand through a bounded page cache,
+//! and decodes documents only when index traversal reaches them.
+
+use super::operations::{IteratorContext, iterator};
+use super::parser::collection_page::RawCollectionPage;
+use super::parser::header_page::HeaderPage;
+use super::parser::raw_data_block::RawDataBlock;
+use super::parser::raw_index_node::RawIndexNode;
+use super::*;
+use crate::ParseError;
+use crate::bson::TotalOrd;
+use crate::buffer_reader::BufferReader;
+use crate::constants::{MAX_LEVEL_LENGTH, PAGE_SIZE};
+use crate::file_io::page::{PageBuffer, PageType};
+use crate::utils::{CaseInsensitiveStr, Collation, PageAddress};
+use std::collections::{HashMap, VecDeque};
+use std::fs::File;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+/// The default number of pages kept in the page cache. (8 MiB)
+const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// The read-only database handle that loads pages on demand.
+///
+/// Unlike [`LiteDBFile`], documents are returned as owned values since they are decoded on each access.
+/// The log file (`-log.db` file) is not applied.
+pub struct LazyLiteDBFile<R> {
+    collections: IndexMap<CaseInsensitiveString, LazyCollection>,
+    creation_time: bson::DateTime,
+    pragmas: EnginePragmas,
+    pages: PageCache<R>,
+}
+
+struct LazyCollection {
+    indexes: HashMap<String, LazyIndex>,
+}
+
+struct LazyIndex {
+    head: PageAddress,
+    tail: PageAddress,
+}
+
+/// The index node read from the page. head / tail node have MinValue / MaxValue as key
+struct LazyIndexNode {
+    key: bson::Value,
+    data_block: PageAddress,
+    prev: Vec<PageAddress>,
+    next: Vec<PageAddress>,
+}
+
+impl LazyIndexNode {
+    fn get_next_prev(&self, level: u8, order: InternalOrder) -> Option<PageAddress> {
+        let address = match order {
+            InternalOrder::Ascending => self.next.get(level as usize),
+            InternalOrder::Descending => self.prev.get(level as usize),
+        };
+        address.copied().filter(|x| !x.is_empty())
+    }
+}
+
+impl LazyLiteDBFile<File> {
+    /// Opens the database file at `path`.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(File::open(path)?)
+    }
+}
+
+impl<R: Read + Seek> LazyLiteDBFile<R> {
+    /// Creates the database handle reading from `reader` with default cache capacity.
+    pub fn new(reader: R) -> io::Result<Self> {
+        Self::with_cache_capacity(reader, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Creates the database handle reading from `reader` that caches up to `capacity` pages.
+    pub fn with_cache_capacity(mut reader: R, capacity: usize) -> io::Result<Self> {
+        let page_count = (reader.seek(SeekFrom::End(0))? / PAGE_SIZE as u64) as u32;
+
+        if page_count != 0 {
+            // password protected file starts with encryption marker instead of page id,
+            // so we have to check this before reading the header page as a page
+            let mut marker = [0u8; 1];
+            reader.seek(SeekFrom::Start(0))?;
+            reader.read_exact(&mut marker)?;
+            if marker[0] == 1 {
+                return Err(ParseError::encrypted().into());
+            }
+        }
+
+        let pages = PageCache {
+            inner: Mutex::new(PageCacheInner {
+                reader,
+                capacity: capacity.max(1),
+                pages: HashMap::new(),
+                recently_used: VecDeque::new(),
+                clock: 0,
+            }),
+            page_count,
+        };
+
+        if page_count == 0 {
+            return Ok(Self {
+                collections: IndexMap::new(),
+                creation_time: bson::DateTime::now(),
+                pragmas: EnginePragmas::default(),
+                pages,
+            });
+        }
+
+        let header_page = pages.get(0)?;
+
+        let header =
+            HeaderPage::parse(PageBuffer::new(&header_page)).map_err(|e| e.with_page(0))?;
+
+        let mut collections = IndexMap::new();
+
+        for (key, page) in header.collections.iter() {
+            let page = page.as_i32().ok_or_else(ParseError::invalid_database)? as u32;
+            let page_buffer = pages.get(page)?;
+            let collection = RawCollectionPage::parse(PageBuffer::new(&page_buffer))
+                .map_err(|e| e.with_page(page).with_collection(key))?;
+
+            let indexes = collection
+                .indexes
+                .into_iter()
+                .map(|(name, index)| {
+                    let index = LazyIndex {
+                        head: index.head,
+                        tail: index.tail,
+                    };
+                    (name, index)
+                })
+                .collect::<HashMap<_, _>>();
+
+            if !indexes.contains_key("_id") {
+                return Err(ParseError::no_id_index()
+                    .with_page(page)
+                    .with_collection(key)
+                    .into());
+            }
+
+            collections.insert(
+                CaseInsensitiveString(key.to_string()),
+                LazyCollection { indexes },
+            );
+        }
+
+        Ok(Self {
+            collections,
+            creation_time: header.creation_time,
+            pragmas: header.pragmas,
+            pages,
+        })
+    }
+
+    pub fn creation_time(&self) -> bson::DateTime {
+        self.creation_time
+    }
+
+    pub fn get_collection_names(&self) -> Vec<String> {
+        self.collections.keys().cloned().map(|x| x.0).collect()
+    }
+
+    fn read_index_node(
+        &self,
+        index: &LazyIndex,
+        address: PageAddress,
+    ) -> io::Result<LazyIndexNode> {
+        let page = self.pages.get_typed(address.page_id(), PageType::Index)?;
+        let page = PageBuffer::new(&page);
+        if !page.block_exists(address.index()) {
+            return Err(ParseError::bad_reference().with_address(address).into());
+        }
+        let raw = RawIndexNode::parse(page.get_block(address.index()))
+            .map_err(|e| e.with_address(address))?;
+
+        let key = if address == index.head {
+            bson::Value::MinValue
+        } else if address == index.tail {
+            bson::Value::MaxValue
+        } else {
+            raw.key
+        };
+
+        Ok(LazyIndexNode {
+            key,
+            data_block: raw.data_block,
+            prev: raw.prev,
+            next: raw.next,
+        })
+    }
+
+    fn read_document(&self, address: PageAddress) -> io::Result<bson::Document> {
+        let mut pages = vec![];
+
+        let mut cur = address;
+        while !cur.is_empty() {
+            if pages.len() > self.pages.page_count as usize {
+                return Err(ParseError::bad_reference().with_address(cur).into());
+            }
+            let page = self.pages.get_typed(cur.page_id(), PageType::Data)?;
+            let buffer = PageBuffer::new(&page);
+            if !buffer.block_exists(cur.index()) {
+                return Err(ParseError::bad_reference().with_address(cur).into());
+            }
+            let next = RawDataBlock::parse(buffer.get_block(cur.index())).next_block();
+            pages.push((page, cur.index()));
+            cur = next;
+        }
+
+        let buffers = pages
+            .iter()
+            .map(|(page, index)| {
+                RawDataBlock::parse(PageBuffer::new(page).get_block(*index)).buffer()
+            })
+            .collect::<Vec<_>>();
+
+        let mut reader = BufferReader::fragmented(buffers);
+        let document = reader
+            .read_document()
+            .map_err(|e| e.with_address(address).with_offset(reader.position()))?;
+        Ok(document)
+    }
+
+    /// Finds the node like [`IndexHelper::find`]
+    fn find(
+        &self,
+        collation: &Collation,
+        index: &LazyIndex,
+        value: &bson::Value,
+        sibling: bool,
+        order: InternalOrder,
+    ) -> io::Result<Option<LazyIndexNode>> {
+        let mut left_node = if order == InternalOrder::Ascending {
+            self.read_index_node(index, index.head)?
+        } else {
+            self.read_index_node(index, index.tail)?
+        };
+
+        for level in (0..=(MAX_LEVEL_LENGTH - 1)).rev() {
+            let mut right = left_node.get_next_prev(level, order);
+
+            let mut counter = 0;
+            while let Some(right_key) = right {
+                if counter > self.pages.max_blocks() {
+                    return Err(ParseError::bad_reference().into());
+                }
+                counter += 1;
+
+                let right_node = self.read_index_node(index, right_key)?;
+
+                let diff = collation.compare(&right_node.key, value);
+
+                if order == diff && (level > 0 || !sibling) {
+                    break; // go down one level
+                }
+
+                if order == diff && level == 0 && sibling {
+                    // is head/tail?
+                    if is_edge(&right_node.key) {
+                        return Ok(None);
+                    } else {
+                        return Ok(Some(right_node));
+                    };
+                }
+
+                // if equals, return index node
+                if diff.is_eq() {
+                    return Ok(Some(right_node));
+                }
+
+                right = right_node.get_next_prev(level, order);
+                left_node = right_node;
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn find_range_by_index(
+        &self,
+        collection: &str,
+        index: &str,
+        min_inclusive: &bson::Value,
+        max_inclusive: &bson::Value,
+        order: Order,
+    ) -> impl Iterator<Item = io::Result<bson::Document>> {
+        iterator(
+            async move |ctx: IteratorContext<io::Result<bson::Document>>| {
+                if max_inclusive.total_cmp(min_inclusive).is_lt() {
+                    return;
+                }
+
+                let Some(collection) = self.collections.get(CaseInsensitiveStr::new(collection))
+                else {
+                    return;
+                };
+
+                let collation = self.pragmas.collation;
+
+                let Some(index) = collection.indexes.get(index) else {
+                    return;
+                };
+
+                let (start, end) = match order {
+                    Order::Ascending => (min_inclusive, max_inclusive),
+                    Order::Descending => (max_inclusive, min_inclusive),
+                };
+                let order = order.to_internal();
+
+                macro_rules! tri {
+                    ($expr: expr) => {
+                        match $expr {
+                            Ok(value) => value,
+                            Err(e) => {
+                                ctx.yields(Err(e)).await;
+                                return;
+                            }
+                        }
+                    };
+                }
+
+                let first = match start {
+                    bson::Value::MinValue => Some(tri!(self.read_index_node(index, index.head))),
+                    bson::Value::MaxValue => Some(tri!(self.read_index_node(index, index.tail))),
+                    start => tri!(self.find(&collation, index, start, true, order)),
+                };
+
+                let Some(first) = first else {
+                    return;
+                };
+
+                // going backward in same value list to get first value
+                let mut prev = first.get_next_prev(0, -order);
+                while let Some(address) = prev {
+                    let node = tri!(self.read_index_node(index, address));
+                    if is_edge(&node.key) || collation.compare(&node.key, start).is_ne() {
+                        break;
+                    }
+                    ctx.yields(self.read_document(node.data_block)).await;
+                    prev = node.get_next_prev(0, -order);
+                }
+
+                let mut node = Some(first);
+
+                // returns (or not) equals start value
+                while let Some(cur_node) = node.take() {
+                    let diff = collation.compare(&cur_node.key, start);
+
+                    // if current value are not equals start, go out this loop
+                    if diff.is_ne() {
+                        node = Some(cur_node);
+                        break;
+                    }
+
+                    if !is_edge(&cur_node.key) {
+                        ctx.yields(self.read_document(cur_node.data_block)).await;
+                    }
+
+                    if let Some(next) = cur_node.get_next_prev(0, order) {
+                        node = Some(tri!(self.read_index_node(index, next)));
+                    }
+                }
+
+                // navigate using next[0] do next node - if less or equals returns
+                while let Some(cur_node) = node.take() {
+                    let diff = collation.compare(&cur_node.key, end);
+
+                    if is_edge(&cur_node.key) || order == diff {
+                        break;
+                    } else {
+                        ctx.yields(self.read_document(cur_node.data_block)).await;
+                    }
+
+                    if let Some(next) = cur_node.get_next_prev(0, order) {
+                        node = Some(tri!(self.read_index_node(index, next)));
+                    }
+                }
+            },
+        )
+    }
+
+    pub fn get_all(&self, collection: &str) -> impl Iterator<Item = io::Result<bson::Document>> {
+        self.find_range_by_index(
+            collection,
+            "_id",
+            &bson::Value::MinValue,
+            &bson::Value::MaxValue,
+            Order::Ascending,
+        )
+    }
+
+    pub fn get_range_indexed(
+        &self,
+        collection: &str,
+        index: &str,
+        min_inclusive: &bson::Value,
+        max_inclusive: &bson::Value,
+        order: Order,
+    ) -> impl Iterator<Item = io::Result<bson::Document>> {
+        self.find_range_by_index(collection, index, min_inclusive, max_inclusive, order)
+    }
+
+    pub fn get_by_index(
+        &self,
+        collection: &str,
+        index: &str,
+        find: &bson::Value,
+    ) -> impl Iterator<Item = io::Result<bson::Document>> {
+        self.find_range_by_index(collection, index, find, find, Order::Ascending)
+    }
+}
+
+fn is_edge(this: &bson::Value) -> bool {
+    matches!(this, bson::Value::MinValue | bson::Value::MaxValue)
+}
+
+/// The LRU cache of pages read from the reader.
+struct PageCache<R> {
+    inner: Mutex<PageCacheInner<R>>,
+    page_count: u32,
+}
+
+struct PageCacheInner<R> {
+    reader: R,
+    capacity: usize,
+    pages: HashMap<u32, CachedPage>,
+    /// The page ids in the order of use, with the clock at the use.
+    ///
+    /// Entries older than `last_used` of the page are stale and skipped on eviction.
+    recently_used: VecDeque<(u32, u64)>,
+    clock: u64,
+}
+
+struct CachedPage {
+    buffer: Arc<[u8]>,
+    last_used: u64,
+}
+
+impl<R: Read + Seek> PageCache<R> {
+    /// Upper bound of the number of blocks in the file, used for loop detection
+    fn max_blocks(&self) -> usize {
+        self.page_count as usize * 256
+    }
+
+    fn get_typed(&self, page_id: u32, page_type: PageType) -> io::Result<Arc<[u8]>> {
+        let page = self.get(page_id)?;
+        if PageBuffer::new(&page).page_type() != Some(page_type) {
+            return Err(ParseError::bad_reference().with_page(page_id).into());
+        }
+        Ok(page)
+    }
+
+    fn get(&self, page_id: u32) -> io::Result<Arc<[u8]>> {
+        if page_id >= self.page_count {
+            return Err(ParseError::bad_reference().with_page(page_id).into());
+        }
+
+        let mut inner = self.inner.lock().unwrap();
+        inner.clock += 1;
+        let clock = inner.clock;
+
+        if let Some(page) = inner.pages.get_mut(&page_id) {
+            page.last_used = clock;
+            let buffer = page.buffer.clone();
+            inner.used(page_id, clock);
+            return Ok(buffer);
+        }
+
+        let buffer = inner.read_page(page_id)?;
+
+        if inner.pages.len() >= inner.capacity {
+            inner.evict();
+        }
+
+        inner.pages.insert(
+            page_id,
+            CachedPage {
+                buffer: buffer.clone(),
+                last_used: clock,
+            },
+        );
+        inner.used(page_id, clock);
+
+        Ok(buffer)
+    }
+}
+
+impl<R> PageCacheInner<R> {
+    fn is_stale(&self, (page_id, clock): (u32, u64)) -> bool {
+        self.pages
+            .get(&page_id)
+            .is_none_or(|page| page.last_used != clock)
+    }
+
+    fn used(&mut self, page_id: u32, clock: u64) {
+        self.recently_used.push_back((page_id, clock));
+
+        // remove stale entries not to grow the queue unboundedly.
+        // this runs after at least `capacity` uses so the amortized cost is constant.
+        if self.recently_used.len() > self.capacity * 2 {
+            let mut recently_used = std::mem::take(&mut self.recently_used);
+            recently_used.retain(|&entry| !self.is_stale(entry));
+            self.recently_used = recently_used;
+        }
+    }
+
+    /// Removes the least recently used page
+    fn evict(&mut self) {
+        while let Some(entry) = self.recently_used.pop_front() {
+            if !self.is_stale(entry) {
+                self.pages.remove(&entry.0);
+                return;
+            }
+        }
+    }
+}
+
+impl<R: Read + Seek> PageCacheInner<R> {
+    fn read_page(&mut self, page_id: u32) -> io::Result<Arc<[u8]>> {
+        let mut buffer = vec![0u8; PAGE_SIZE];
+        self.reader
+            .seek(SeekFrom::Start(page_id as u64 * PAGE_SIZE as u64))?;
+        self.reader.read_exact(&mut buffer)?;
+
+        let page = PageBuffer::new(&buffer);
+        if page.page_id() != page_id || page.page_type().is_none() {
+            return Err(ParseError::invalid_page(page_id).into());
+        }
+
+        Ok(buffer.into())
+    }
+}
+
+#[allow(dead_code)]
+fn _type_check() {
+    use crate::utils::checker::*;
+
+    check_sync_send(dummy::<LazyLiteDBFile<File>>());
+}
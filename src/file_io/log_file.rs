@@ -0,0 +1,182 @@
+//! Support for the `-log.db` WAL file of LiteDB.
+//!
+//! LiteEngine never writes to the data file directly. Each transaction appends its dirty pages
+//! to the log file, and the last page of a transaction is marked as confirmed.
+//! Later, checkpoint copies the latest confirmed version of each page to the data file.
+//!
+//! This module replays the log file the same way as `WalIndexService.RestoreIndex` + checkpoint does,
+//! and creates log file pages for changes made on [`LiteDBFile`].
+
+use super::*;
+use crate::constants::PAGE_SIZE;
+use crate::file_io::page::{PageBuffer, PageType};
+use crate::file_io::writer;
+use crate::{ParseError, ParseResult};
+use std::collections::HashMap;
+
+impl LiteDBFile {
+    /// Parses the data file with the log file (`-log.db` file) applied.
+    ///
+    /// Pages of transactions that are not confirmed in the log file will be ignored like LiteEngine does.
+    pub fn parse_with_log(data: &[u8], log: &[u8]) -> ParseResult<Self> {
+        parse_with_log(data, log)
+    }
+
+    /// Creates the pages to be appended to the log file (`-log.db` file).
+    ///
+    /// The returned pages contain all pages changed since this file is loaded (or this function is called last time)
+    /// as a single confirmed transaction.
+    /// Those changes will be applied to the data file at next checkpoint of LiteEngine.
+    ///
+    /// Returns empty `Vec` if nothing is changed.
+    ///
+    /// Returns [`ErrorKind::EncryptedLog`](crate::ErrorKind::EncryptedLog) if the password is set
+    /// since writing encrypted log file is not supported.
+    pub fn serialize_log(&mut self) -> crate::Result<Vec<u8>> {
+        #[cfg(feature = "encryption")]
+        if self.password.is_some() {
+            return Err(crate::Error::encrypted_log());
+        }
+        let (log, written) = write_log(self);
+        if !log.is_empty() {
+            self.loaded_pages = written.commit(self).into();
+            self.last_transaction_id += 1;
+        }
+        Ok(log)
+    }
+}
+
+pub(super) fn parse_with_log(data: &[u8], log: &[u8]) -> ParseResult<LiteDBFile> {
+    let log = &log[..(log.len() & !(PAGE_SIZE - 1))];
+
+    let mut parsed = parser::parse(&apply_log(data, log)?)?;
+
+    // we use all pages, including unconfirmed ones, to avoid reusing the transaction id
+    parsed.last_transaction_id = log
+        .chunks(PAGE_SIZE)
+        .map(PageBuffer::new)
+        .map(|page| page.transaction_id())
+        .filter(|&id| id != u32::MAX)
+        .max()
+        .unwrap_or(0);
+
+    Ok(parsed)
+}
+
+/// Returns the log pages and the new data file image
+fn write_log(file: &LiteDBFile) -> (Vec<u8>, writer::Written) {
+    let written = writer::write(file);
+    let transaction_id = file.last_transaction_id + 1;
+
+    let mut changed = written
+        .pages
+        .chunks(PAGE_SIZE)
+        .map(PageBuffer::new)
+        .enumerate()
+        .filter(|&(page_id, page)| {
+            page_id == 0
+                || file
+                    .loaded_pages
+                    .get(page_id * PAGE_SIZE..(page_id + 1) * PAGE_SIZE)
+                    .is_none_or(|loaded| !PageBuffer::new(loaded).same_content(page))
+        })
+        .map(|(_, page)| page)
+        .collect::<Vec<_>>();
+
+    // header page is always included to confirm the transaction.
+    // if header page is the only page and not changed, there is nothing to write.
+    if changed.len() == 1
+        && file
+            .loaded_pages
+            .get(..PAGE_SIZE)
+            .is_some_and(|loaded| PageBuffer::new(loaded).same_content(changed[0]))
+    {
+        return (Vec::new(), written);
+    }
+
+    // header page will be written at last to confirm the transaction
+    changed.rotate_left(1);
+
+    let mut log = Vec::with_capacity(changed.len() * PAGE_SIZE);
+
+    for (index, page) in changed.iter().enumerate() {
+        log.extend_from_slice(page.as_bytes());
+        let page = PageBuffer::new_mut(&mut log[index * PAGE_SIZE..][..PAGE_SIZE]);
+        page.set_transaction_id(transaction_id);
+        page.set_confirmed(index == changed.len() - 1);
+    }
+
+    (log, written)
+}
+
+/// Creates the data file image with confirmed pages in the log applied.
+///
+/// Returns the invalid page error if a page in the log is placed far beyond the end of the data file.
+pub(super) fn apply_log(data: &[u8], log: &[u8]) -> ParseResult<Vec<u8>> {
+    // if the length is not multiple of PAGE_SIZE, crop
+    let data = &data[..(data.len() & !(PAGE_SIZE - 1))];
+    let log = &log[..(log.len() & !(PAGE_SIZE - 1))];
+
+    let confirmed = confirmed_pages(log);
+
+    // each log page adds at most one page to the data file,
+    // so page id beyond this is corrupted and resizing to it may exhaust the memory.
+    let max_pages = (data.len() + log.len()) / PAGE_SIZE;
+    if let Some(&page_id) = confirmed.keys().find(|&&id| id as usize >= max_pages) {
+        return Err(ParseError::invalid_page(page_id));
+    }
+
+    let mut image = data.to_vec();
+
+    for (&page_id, &log_page) in &confirmed {
+        let offset = page_id as usize * PAGE_SIZE;
+        if image.len() < offset + PAGE_SIZE {
+            // the log file may contain pages after the end of data file
+            image.resize(offset + PAGE_SIZE, 0);
+        }
+
+        let page = PageBuffer::new_mut(&mut image[offset..][..PAGE_SIZE]);
+        page.as_bytes_mut().copy_from_slice(log_page.as_bytes());
+
+        // checkpoint clears transaction information when copying to data file
+        page.set_transaction_id(u32::MAX);
+        page.set_confirmed(false);
+    }
+
+    // pages between data file end and log pages are never written; initialize as empty page.
+    // zero pages in the data file are left as is so that corruption is not masked
+    let data_pages = data.len() / PAGE_SIZE;
+    for (page_id, page) in image.chunks_mut(PAGE_SIZE).enumerate().skip(data_pages) {
+        if page.iter().all(|&x| x == 0) && page_id != 0 {
+            PageBuffer::new_mut(page).initialize_page(page_id as u32, PageType::Empty);
+        }
+    }
+
+    Ok(image)
+}
+
+/// Returns the latest confirmed page for each page id in the log file.
+fn confirmed_pages(log: &[u8]) -> HashMap<u32, &PageBuffer> {
+    let mut transactions = HashMap::<u32, Vec<&PageBuffer>>::new();
+    let mut confirmed = HashMap::<u32, &PageBuffer>::new();
+
+    for page in log.chunks(PAGE_SIZE).map(PageBuffer::new) {
+        // skip blank pages
+        if page.as_bytes().iter().all(|&x| x == 0) {
+            continue;
+        }
+
+        let transaction_id = page.transaction_id();
+        let pages = transactions.entry(transaction_id).or_default();
+        pages.push(page);
+
+        if page.is_confirmed() {
+            // later pages overwrite older version of the page
+            for page in transactions.remove(&transaction_id).unwrap() {
+                confirmed.insert(page.page_id(), page);
+            }
+        }
+    }
+
+    confirmed
+}
@@ -1,7 +1,10 @@
+use crate::Error;
 use crate::file_io::LiteDBFile;
-use crate::utils::{CaseInsensitiveStr, CaseInsensitiveString};
+use crate::file_io::offsets::header_page::COLLECTIONS_SIZE;
+use crate::utils::{CaseInsensitiveStr, CaseInsensitiveString, StrExtension};
 use std::collections::HashSet;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum RenameCollectionResult {
     Renamed,
     SameName,
@@ -43,32 +46,86 @@ impl LiteDBFile {
         true
     }
 
-    pub fn rename_collection(&mut self, old_name: &str, new_name: &str) -> RenameCollectionResult {
+    /// Renames the collection.
+    ///
+    /// # Errors
+    /// This function will return an error if `new_name` is not valid as a collection name.
+    pub fn rename_collection(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+    ) -> crate::Result<RenameCollectionResult> {
         if old_name == new_name {
-            return RenameCollectionResult::SameName;
+            return Ok(RenameCollectionResult::SameName);
         }
 
-        // TODO: check for collection new name
-
         if self
             .collections
             .contains_key(CaseInsensitiveStr::new(new_name))
         {
-            return RenameCollectionResult::NewNameAlreadyExists;
+            return Ok(RenameCollectionResult::NewNameAlreadyExists);
         }
 
-        let Some(collection) = self
+        if !self
+            .collections
+            .contains_key(CaseInsensitiveStr::new(old_name))
+        {
+            return Ok(RenameCollectionResult::OldNotExists);
+        }
+
+        self.check_collection_name(new_name, Some(old_name))?;
+
+        let collection = self
             .collections
             .shift_remove(CaseInsensitiveStr::new(old_name))
-        else {
-            return RenameCollectionResult::OldNotExists;
-        };
+            .unwrap();
 
         let result = self
             .collections
             .insert(CaseInsensitiveString(new_name.to_string()), collection);
         debug_assert!(result.is_none());
 
-        RenameCollectionResult::Renamed
+        Ok(RenameCollectionResult::Renamed)
+    }
+
+    /// Checks the collection can be created with `name` if it does not exist.
+    pub(in crate::file_io) fn check_new_collection(&self, name: &str) -> crate::Result<()> {
+        if self.collections.contains_key(CaseInsensitiveStr::new(name)) {
+            return Ok(());
+        }
+        self.check_collection_name(name, None)
+    }
+
+    /// Checks the `name` is valid for a collection, like `CheckName` of LiteDB.
+    ///
+    /// The collection `replacing` is excluded when calculating the space in the header page.
+    fn check_collection_name(&self, name: &str, replacing: Option<&str>) -> crate::Result<()> {
+        if name.is_empty() || !name.is_word() {
+            return Err(Error::invalid_collection_name(name, "use only [a-Z$_]"));
+        }
+        if name.starts_with('$') {
+            return Err(Error::invalid_collection_name(
+                name,
+                "collection can't starts with `$` (reserved for system collections)",
+            ));
+        }
+
+        // the names are stored in the header page as a document of page ids
+        let entry_len = |name: &str| 1 + (name.len() + 1) + 4; // tag, cstring key, and int32
+        let used = 4 + 1 // the length and the trailing zero of the document
+            + self
+                .collections
+                .keys()
+                .filter(|x| Some(x.0.as_str()) != replacing)
+                .map(|x| entry_len(&x.0))
+                .sum::<usize>();
+        if used + entry_len(name) > COLLECTIONS_SIZE {
+            return Err(Error::invalid_collection_name(
+                name,
+                "there is no space in header for this collection name",
+            ));
+        }
+
+        Ok(())
     }
 }
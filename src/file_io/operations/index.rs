@@ -2,18 +2,74 @@ use crate::Error;
 use crate::constants::INDEX_NAME_MAX_LENGTH;
 use crate::expression::{BsonExpression, ExecutionScope};
 use crate::file_io::index_helper::IndexHelper;
-use crate::file_io::{Collection, LiteDBFile};
-use crate::utils::{CaseInsensitiveStr, CaseInsensitiveString, Collation, Order, StrExtension};
+use crate::file_io::offsets::collection_page::P_INDEXES_COUNT;
+use crate::file_io::{BsonAutoId, Collection, LiteDBFile};
+use crate::utils::{
+    CaseInsensitiveStr, CaseInsensitiveString, Collation, KeyArena, Order, PageAddress,
+    StrExtension,
+};
 use indexmap::IndexMap;
 
+/// The definition of an index, returned by [`LiteDBFile::indexes`].
+///
+/// This has the same information as documents in `$indexes` system collection of LiteDB.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexInfo {
+    pub collection: String,
+    pub slot: u8,
+    /// The index type. LiteDB always uses `0` (skip list).
+    pub index_type: u8,
+    pub name: String,
+    /// The source of the index expression.
+    pub expression: String,
+    pub unique: bool,
+    /// The highest skip list level of nodes in this index.
+    pub max_level: u8,
+}
+
 impl LiteDBFile {
-    /// # Panics
-    /// This function will panics if
+    /// Returns the indexes of the collection in slot order.
+    ///
+    /// Returns empty `Vec` if the collection does not exist.
+    pub fn indexes(&self, collection: &str) -> Vec<IndexInfo> {
+        let Some((name, collection)) = self
+            .collections
+            .get_key_value(CaseInsensitiveStr::new(collection))
+        else {
+            return vec![];
+        };
+
+        let mut indexes = collection
+            .indexes
+            .values()
+            .map(|index| IndexInfo {
+                collection: name.0.clone(),
+                slot: index.slot,
+                index_type: index.index_type,
+                name: index.name.clone(),
+                expression: index.expression.clone(),
+                unique: index.unique,
+                max_level: IndexHelper::find_all(&self.index_arena, index, Order::Ascending)
+                    .into_iter()
+                    .map(|x| self.index_arena[x].levels)
+                    .max()
+                    .unwrap_or(0),
+            })
+            .collect::<Vec<_>>();
+        indexes.sort_by_key(|x| x.slot);
+        indexes
+    }
+
+    /// # Errors
+    /// This function will return an error if
     /// - the `name` is not valid (not a word or starting with '$', or too long)
     /// - the `expression` is not suitable for index; this means:
     ///   - the `expression` does not read any fields from source
     ///   - the `expression` is NOT deterministic; like time dependent or uses randomness.
     ///   - the `expression` consumes multiple values, but `unique` is enabled
+    /// - the `collection` does not exist and the name is not valid for a new collection
+    /// - the collection page has no space for the new index
+    /// - the key of any existing document is too long for the index
     pub fn ensure_index(
         &mut self,
         collection: &str,
@@ -21,23 +77,21 @@ impl LiteDBFile {
         expression: BsonExpression,
         unique: bool,
     ) -> crate::Result<bool> {
-        assert!(
-            !name.is_empty()
-                && name.is_word()
-                && !name.starts_with('$')
-                && name.len() < INDEX_NAME_MAX_LENGTH,
-            "invalid index name"
-        );
-        assert!(expression.is_indexable(), "invalid expression");
-        assert!(
-            expression.is_scalar() || !unique,
-            "scalar expression is needed for unique index"
-        );
+        check_index_definition(name, &expression, unique)
+            .map_err(|reason| Error::invalid_index(name, reason))?;
 
         if expression.source() == "$._id" {
             return Ok(false); // always exists
         }
 
+        self.check_new_collection(collection)?;
+
+        let current = self.collections.get(CaseInsensitiveStr::new(collection));
+        if current.is_none_or(|x| !x.indexes.contains_key(name)) {
+            check_index_space(current, name, &expression)
+                .map_err(|reason| Error::invalid_index(name, reason))?;
+        }
+
         let collection = self
             .collections
             .entry(CaseInsensitiveString(collection.into()))
@@ -63,41 +117,61 @@ impl LiteDBFile {
             let exec_context = ExecutionScope::new(self.pragmas.collation);
 
             let pk_index = collection.pk_index();
-            for pk_key in IndexHelper::find_all(&self.index_arena, pk_index, Order::Ascending) {
-                let data_key = self.index_arena[pk_key].data.unwrap();
-                let doc = self.data[data_key].data.clone().into();
-
-                for key in exec_context.get_index_keys(&expression, &doc) {
-                    let key = key?;
-                    IndexHelper::add_node(
-                        &mut self.index_arena,
-                        &mut self.data,
-                        &self.pragmas.collation,
-                        index,
-                        key.clone(),
-                        data_key,
-                    )?;
+            let result = (|| {
+                for pk_key in IndexHelper::find_all(&self.index_arena, pk_index, Order::Ascending) {
+                    let data_key = self.index_arena[pk_key].data.unwrap();
+                    let doc = self.data[data_key].data.clone().into();
+
+                    for key in exec_context.get_index_keys(&expression, &doc) {
+                        let key = key?;
+                        IndexHelper::add_node(
+                            &mut self.index_arena,
+                            &mut self.data,
+                            &mut self.random,
+                            &self.pragmas.collation,
+                            index,
+                            key.clone(),
+                            data_key,
+                        )?;
+                    }
                 }
+                Ok(())
+            })();
+
+            if let Err(e) = result {
+                // remove the partially built index to keep the database unchanged
+                let index = collection.indexes.shift_remove(name).unwrap();
+                IndexHelper::drop_index(
+                    &mut self.index_arena,
+                    &mut self.data,
+                    collection.pk_index(),
+                    index,
+                );
+                return Err(e);
             }
 
             Ok(true)
         }
     }
 
-    /// # Panics
-    /// This function will panics if you're dropping primary index, in other words index named `"_id"`
-    pub fn drop_index(&mut self, collection: &str, name: &str) -> bool {
-        assert!(name != "_id", "dropping primary key index");
+    /// Drops the index, and returns `false` if the index does not exist.
+    ///
+    /// # Errors
+    /// This function will return an error if you're dropping primary index, in other words index named `"_id"`
+    pub fn drop_index(&mut self, collection: &str, name: &str) -> crate::Result<bool> {
+        if name == "_id" {
+            return Err(Error::drop_id_index());
+        }
 
         let Some(collection) = self
             .collections
             .get_mut(CaseInsensitiveStr::new(collection))
         else {
-            return false;
+            return Ok(false);
         };
 
         let Some(index) = collection.indexes.shift_remove(name) else {
-            return false;
+            return Ok(false);
         };
 
         IndexHelper::drop_index(
@@ -107,11 +181,69 @@ impl LiteDBFile {
             index,
         );
 
-        true
+        Ok(true)
+    }
+
+    /// Rebuilds all indexes with `collation`, and changes the collation of this database.
+    ///
+    /// If any index cannot be rebuilt, like duplicated keys in unique index under the new collation,
+    /// this database is not modified.
+    pub(in crate::file_io) fn rebuild_indexes(
+        &mut self,
+        collation: Collation,
+    ) -> crate::Result<()> {
+        let mut index_arena = KeyArena::new();
+        let mut data_arena = KeyArena::new();
+        let mut collections = IndexMap::new();
+
+        for (name, collection) in &self.collections {
+            let mut rebuilt = Collection::new(&mut index_arena);
+            rebuilt.page_id = collection.page_id;
+
+            for index in collection.indexes.values() {
+                if index.name == "_id" {
+                    continue;
+                }
+                IndexHelper::create_index(
+                    &mut index_arena,
+                    &mut rebuilt,
+                    &index.name,
+                    index.bson_expr.clone(),
+                    index.unique,
+                );
+            }
+
+            let pk_index = collection.pk_index();
+            for pk_key in IndexHelper::find_all(&self.index_arena, pk_index, Order::Ascending) {
+                let document = &self.data[self.index_arena[pk_key].data.unwrap()];
+
+                let data_key = Self::insert_document(
+                    &mut index_arena,
+                    &mut data_arena,
+                    &mut self.random,
+                    collation,
+                    &mut rebuilt,
+                    document.data.clone(),
+                    BsonAutoId::ObjectId,
+                )?;
+
+                // the document itself is not changed so we can keep the data blocks
+                data_arena[data_key].position = document.position;
+            }
+
+            collections.insert(name.clone(), rebuilt);
+        }
+
+        self.index_arena = index_arena;
+        self.data = data_arena;
+        self.collections = collections;
+        self.pragmas.collation = collation;
+
+        Ok(())
     }
 
     pub fn drop_indexes_and_update_collation_if_collation_not_supported(&mut self) -> bool {
-        if self.pragmas.collation == Collation::default() {
+        if self.pragmas.collation.is_supported() {
             // the collation is supported so no need to drop
             return false;
         }
@@ -137,3 +269,73 @@ impl LiteDBFile {
         false
     }
 }
+
+/// Checks the index definition is valid for [`LiteDBFile::ensure_index`].
+fn check_index_definition(
+    name: &str,
+    expression: &BsonExpression,
+    unique: bool,
+) -> Result<(), &'static str> {
+    if name.is_empty()
+        || !name.is_word()
+        || name.starts_with('$')
+        || name.len() >= INDEX_NAME_MAX_LENGTH
+    {
+        return Err("invalid index name");
+    }
+    if !expression.is_indexable() {
+        return Err("invalid expression");
+    }
+    if !expression.is_scalar() && unique {
+        return Err("scalar expression is needed for unique index");
+    }
+    Ok(())
+}
+
+/// Checks the collection page has space for the new index entry, like LiteDB does.
+fn check_index_space(
+    collection: Option<&Collection>,
+    name: &str,
+    expression: &BsonExpression,
+) -> Result<(), &'static str> {
+    fn entry_length(name: &str, expression: &str) -> usize {
+        // slot, type, name, expression, unique, head, tail, reserved, and free index page
+        1 + 1
+            + (name.len() + 1)
+            + (expression.len() + 1)
+            + 1
+            + PageAddress::SERIALIZED_SIZE * 2
+            + 1
+            + 4
+    }
+
+    let (count, max_slot, used) = match collection {
+        Some(collection) => (
+            collection.indexes.len(),
+            collection
+                .indexes
+                .values()
+                .map(|x| x.slot)
+                .max()
+                .unwrap_or(0),
+            collection
+                .indexes
+                .values()
+                .map(|x| entry_length(&x.name, &x.expression))
+                .sum(),
+        ),
+        // a new collection only has `_id` index
+        None => (1, 0, entry_length("_id", "$._id")),
+    };
+
+    if count >= u8::MAX as usize || max_slot == u8::MAX {
+        return Err("too many indexes in the collection");
+    }
+
+    // the first byte is the count of indexes
+    if 1 + used + entry_length(name, expression.source()) >= P_INDEXES_COUNT {
+        return Err("no space for the index in the collection page");
+    }
+
+    Ok(())
+}
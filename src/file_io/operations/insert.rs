@@ -1,16 +1,23 @@
+use crate::Error;
 use crate::bson;
+use crate::constants::MAX_DOCUMENT_SIZE;
 use crate::expression::ExecutionScope;
 use crate::file_io::index_helper::IndexHelper;
 use crate::file_io::{BsonAutoId, Collection, DbDocument, IndexNode, LiteDBFile};
-use crate::utils::{CaseInsensitiveString, Collation, KeyArena};
+use crate::utils::{ArenaKey, CaseInsensitiveString, Collation, KeyArena, Randomizer};
 
 impl LiteDBFile {
+    /// Each document is written atomically: if writing a document fails, the document is not written
+    /// but the documents before it are kept. Use [`LiteDBFile::atomically`] to make the whole batch
+    /// all-or-nothing.
     pub fn insert(
         &mut self,
         collection: &str,
         docs: Vec<bson::Document>,
         auto_id: BsonAutoId,
     ) -> crate::Result<usize> {
+        self.check_new_collection(collection)?;
+
         let collection = self
             .collections
             .entry(CaseInsensitiveString(collection.into()))
@@ -22,6 +29,7 @@ impl LiteDBFile {
             Self::insert_document(
                 &mut self.index_arena,
                 &mut self.data,
+                &mut self.random,
                 self.pragmas.collation,
                 collection,
                 doc,
@@ -34,14 +42,15 @@ impl LiteDBFile {
         Ok(count)
     }
 
-    pub(super) fn insert_document(
+    pub(in crate::file_io) fn insert_document(
         index_arena: &mut KeyArena<IndexNode>,
         data_arena: &mut KeyArena<DbDocument>,
+        random: &mut Randomizer,
         collation: Collation,
         collection: &mut Collection,
         mut doc: bson::Document,
         auto_id: BsonAutoId,
-    ) -> crate::Result<()> {
+    ) -> crate::Result<ArenaKey<DbDocument>> {
         // if no _id, use AutoId
         let id = if let Some(id) = doc.try_get("_id") {
             #[cfg(feature = "sequential-index")]
@@ -52,8 +61,8 @@ impl LiteDBFile {
             id
         } else {
             let id = match auto_id {
-                BsonAutoId::ObjectId => bson::Value::ObjectId(bson::ObjectId::new()),
-                BsonAutoId::Guid => bson::Value::Guid(bson::Guid::new()),
+                BsonAutoId::ObjectId => bson::Value::ObjectId(random.object_id()),
+                BsonAutoId::Guid => bson::Value::Guid(random.guid()),
                 #[cfg(feature = "sequential-index")]
                 _ => Self::get_sequence(collection, index_arena, auto_id),
             };
@@ -61,26 +70,69 @@ impl LiteDBFile {
             doc.get("_id")
         };
 
-        assert!(
-            !matches!(
-                id,
-                bson::Value::Null | bson::Value::MinValue | bson::Value::MaxValue
-            ),
-            "_id is not indexable type"
-        );
+        if matches!(
+            id,
+            bson::Value::Null | bson::Value::MinValue | bson::Value::MaxValue
+        ) {
+            return Err(Error::invalid_data_type("_id", id));
+        }
+
+        if let Some(key) = doc.find_invalid_key() {
+            return Err(Error::invalid_document_key(key.to_owned()));
+        }
+
+        let length = doc.get_serialized_value_len();
+        if length > MAX_DOCUMENT_SIZE {
+            return Err(Error::document_too_large(id, length));
+        }
 
         let data_key = data_arena.alloc(DbDocument::new(doc.clone()));
         let doc_value = bson::Value::Document(doc);
 
+        if let Err(e) = Self::add_index_nodes(
+            index_arena,
+            data_arena,
+            random,
+            collation,
+            collection,
+            &doc_value,
+            data_key,
+        ) {
+            // remove the document and index nodes already added to keep the database unchanged
+            let data = data_arena.free(data_key);
+            IndexHelper::delete_all(index_arena, &data.index_nodes);
+            return Err(e);
+        }
+
+        Ok(data_key)
+    }
+
+    fn add_index_nodes(
+        index_arena: &mut KeyArena<IndexNode>,
+        data_arena: &mut KeyArena<DbDocument>,
+        random: &mut Randomizer,
+        collation: Collation,
+        collection: &Collection,
+        doc_value: &bson::Value,
+        data_key: ArenaKey<DbDocument>,
+    ) -> crate::Result<()> {
         let scope = ExecutionScope::new(collation);
 
         // add _id PK index first
         {
             let index = collection.pk_index();
-            for key in scope.get_index_keys(&index.bson_expr.clone(), &doc_value) {
+            for key in scope.get_index_keys(&index.bson_expr.clone(), doc_value) {
                 let key = key?.clone();
 
-                IndexHelper::add_node(index_arena, data_arena, &collation, index, key, data_key)?;
+                IndexHelper::add_node(
+                    index_arena,
+                    data_arena,
+                    random,
+                    &collation,
+                    index,
+                    key,
+                    data_key,
+                )?;
             }
         }
 
@@ -88,10 +140,18 @@ impl LiteDBFile {
             if index.name == "_id" {
                 continue;
             }
-            for key in scope.get_index_keys(&index.bson_expr.clone(), &doc_value) {
+            for key in scope.get_index_keys(&index.bson_expr.clone(), doc_value) {
                 let key = key?.clone();
 
-                IndexHelper::add_node(index_arena, data_arena, &collation, index, key, data_key)?;
+                IndexHelper::add_node(
+                    index_arena,
+                    data_arena,
+                    random,
+                    &collation,
+                    index,
+                    key,
+                    data_key,
+                )?;
             }
         }
 
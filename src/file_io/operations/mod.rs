@@ -8,4 +8,7 @@ mod sequence;
 mod update;
 mod upsert;
 
+pub use collections::RenameCollectionResult;
+pub use index::IndexInfo;
 pub use query::Order;
+pub(super) use query::{IteratorContext, iterator};
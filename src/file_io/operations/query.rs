@@ -16,7 +16,7 @@ pub enum Order {
 }
 
 impl Order {
-    fn to_internal(self) -> InternalOrder {
+    pub(in crate::file_io) fn to_internal(self) -> InternalOrder {
         match self {
             Order::Ascending => InternalOrder::Ascending,
             Order::Descending => InternalOrder::Descending,
@@ -31,12 +31,12 @@ static ITERATOR_WAKER_V_TABLE: RawWakerVTable = RawWakerVTable::new(
     |_| (),
 );
 
-struct IteratorContext<T> {
+pub(in crate::file_io) struct IteratorContext<T> {
     phantom: PhantomData<T>,
 }
 
 impl<T: Unpin> IteratorContext<T> {
-    async fn yields(&self, value: T) {
+    pub(in crate::file_io) async fn yields(&self, value: T) {
         struct SuspendOnce<T> {
             value: Option<T>,
         }
@@ -69,7 +69,7 @@ impl<T: Unpin> IteratorContext<T> {
     }
 }
 
-fn iterator<T, F, Fut>(closure: F) -> impl Iterator<Item = T>
+pub(in crate::file_io) fn iterator<T, F, Fut>(closure: F) -> impl Iterator<Item = T>
 where
     F: FnOnce(IteratorContext<T>) -> Fut,
     Fut: Future<Output = ()>,
@@ -137,7 +137,9 @@ impl LiteDBFile {
 
             let indexes = &self.index_arena;
 
-            let index = collection.indexes.get(index).unwrap();
+            let Some(index) = collection.indexes.get(index) else {
+                return;
+            };
 
             let (start, end) = match order {
                 Order::Ascending => (min_inclusive, max_inclusive),
@@ -1,11 +1,15 @@
+use crate::constants::MAX_DOCUMENT_SIZE;
 use crate::expression::ExecutionScope;
 use crate::file_io::index_helper::IndexHelper;
 use crate::file_io::{Collection, DbDocument, IndexNode, LiteDBFile};
-use crate::utils::{CaseInsensitiveStr, Collation, KeyArena, Order};
+use crate::utils::{CaseInsensitiveStr, Collation, KeyArena, Order, Randomizer};
 use crate::{Error, bson};
 use std::collections::HashSet;
 
 impl LiteDBFile {
+    /// Each document is written atomically: if writing a document fails, the document is not written
+    /// but the documents before it are kept. Use [`LiteDBFile::atomically`] to make the whole batch
+    /// all-or-nothing.
     pub fn update(&mut self, collection: &str, docs: Vec<bson::Document>) -> crate::Result<usize> {
         let Some(collection) = self
             .collections
@@ -20,6 +24,7 @@ impl LiteDBFile {
             if Self::update_document(
                 &mut self.index_arena,
                 &mut self.data,
+                &mut self.random,
                 collection,
                 self.pragmas.collation,
                 doc,
@@ -36,6 +41,7 @@ impl LiteDBFile {
     pub(super) fn update_document(
         index_arena: &mut KeyArena<IndexNode>,
         data_arena: &mut KeyArena<DbDocument>,
+        random: &mut Randomizer,
         collection: &Collection,
 
         collation: Collation,
@@ -51,6 +57,15 @@ impl LiteDBFile {
             return Err(Error::invalid_data_type("_id", id));
         }
 
+        if let Some(key) = doc.find_invalid_key() {
+            return Err(Error::invalid_document_key(key.to_owned()));
+        }
+
+        let length = doc.get_serialized_value_len();
+        if length > MAX_DOCUMENT_SIZE {
+            return Err(Error::document_too_large(id, length));
+        }
+
         // find indexNode from pk index
         let Some(pk_node) = IndexHelper::find(
             index_arena,
@@ -64,15 +79,14 @@ impl LiteDBFile {
             return Ok(Some(doc));
         };
 
-        // update data storage
-        data_arena[pk_node.data.unwrap()].data = doc.clone();
+        let pk_data = pk_node.data.unwrap();
 
         // get all current non-pk index nodes from this data block (slot, key, nodePosition)
-        let old_keys = IndexHelper::get_node_list(&data_arena[pk_node.data.unwrap()].index_nodes)
+        let old_keys = IndexHelper::get_node_list(&data_arena[pk_data].index_nodes)
             .map(|x| (index_arena[x].slot, index_arena[x].key.clone(), x))
             .collect::<Vec<_>>();
 
-        let doc_value = bson::Value::Document(doc);
+        let doc_value = bson::Value::Document(doc.clone());
 
         // build a list of all new key index keys
         let mut new_keys: Vec<(u8, &bson::Value, &str)> = vec![];
@@ -89,11 +103,6 @@ impl LiteDBFile {
             }
         }
 
-        if old_keys.is_empty() && new_keys.is_empty() {
-            // early return if no indexes to be updated
-            return Ok(None);
-        }
-
         let to_delete = old_keys
             .iter()
             .filter(|&x| !new_keys.iter().any(|n| n.0 == x.0 && n.1 == &x.1))
@@ -105,27 +114,55 @@ impl LiteDBFile {
             .filter(|x| !old_keys.iter().any(|n| n.0 == x.0 && &n.1 == x.1))
             .collect::<Vec<_>>();
 
-        if to_delete.is_empty() && to_insert.is_empty() {
-            return Ok(None);
+        // RustChange: old nodes are unlinked first since a new key may be equal to the old one
+        // under the collation. they are kept in the arena to restore the previous state if adding fails.
+        let old_nodes = data_arena[pk_data].index_nodes.clone();
+        let unlinked = old_nodes
+            .iter()
+            .copied()
+            .filter(|x| to_delete.contains(x))
+            .collect::<Vec<_>>();
+        for &node in &unlinked {
+            IndexHelper::unlink_node(index_arena, node);
         }
+        data_arena[pk_data]
+            .index_nodes
+            .retain(|x| !to_delete.contains(x));
 
-        let pk_data = pk_node.data.unwrap();
-
-        IndexHelper::delete_list(index_arena, &mut data_arena[pk_data].index_nodes, to_delete);
-
+        let old_node_count = data_arena[pk_data].index_nodes.len();
         for (_, key, name) in to_insert {
             let index = collection.indexes.get(name).unwrap();
 
-            IndexHelper::add_node(
+            if let Err(e) = IndexHelper::add_node(
                 index_arena,
                 data_arena,
+                random,
                 &collation,
                 index,
                 key.clone(),
                 pk_data,
-            )?;
+            ) {
+                let added = data_arena[pk_data].index_nodes.split_off(old_node_count);
+                IndexHelper::delete_all(index_arena, &added);
+
+                for &node in unlinked.iter().rev() {
+                    IndexHelper::relink_node(index_arena, node);
+                }
+                data_arena[pk_data].index_nodes = old_nodes;
+
+                return Err(e);
+            }
+        }
+
+        for node in unlinked {
+            index_arena.free(node);
         }
 
+        // update data storage. the document will be written to new blocks on next write
+        let data = &mut data_arena[pk_data];
+        data.data = doc;
+        data.position = None;
+
         Ok(None)
     }
 }
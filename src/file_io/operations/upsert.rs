@@ -3,12 +3,17 @@ use crate::file_io::{BsonAutoId, Collection, LiteDBFile};
 use crate::utils::CaseInsensitiveString;
 
 impl LiteDBFile {
+    /// Each document is written atomically: if writing a document fails, the document is not written
+    /// but the documents before it are kept. Use [`LiteDBFile::atomically`] to make the whole batch
+    /// all-or-nothing.
     pub fn upsert(
         &mut self,
         collection: &str,
         docs: Vec<bson::Document>,
         auto_id: BsonAutoId,
     ) -> crate::Result<usize> {
+        self.check_new_collection(collection)?;
+
         let collection = self
             .collections
             .entry(CaseInsensitiveString(collection.into()))
@@ -25,6 +30,7 @@ impl LiteDBFile {
                 Self::update_document(
                     &mut self.index_arena,
                     &mut self.data,
+                    &mut self.random,
                     collection,
                     collation,
                     doc,
@@ -37,6 +43,7 @@ impl LiteDBFile {
                 Self::insert_document(
                     &mut self.index_arena,
                     &mut self.data,
+                    &mut self.random,
                     collation,
                     collection,
                     doc,
@@ -125,7 +125,6 @@ impl PageBuffer {
     }
 
     // transaction
-    #[allow(dead_code)]
     pub fn transaction_id(&self) -> u32 {
         self.inner.read_u32(P_TRANSACTION_ID)
     }
@@ -134,7 +133,6 @@ impl PageBuffer {
         self.inner.write_u32(P_TRANSACTION_ID, transaction_id);
     }
 
-    #[allow(dead_code)]
     pub fn is_confirmed(&self) -> bool {
         self.inner.read_bool(P_IS_CONFIRMED)
     }
@@ -143,7 +141,15 @@ impl PageBuffer {
         self.inner.write_bool(P_IS_CONFIRMED, confirmed);
     }
 
-    #[allow(dead_code)]
+    /// Compares the content of the page except for the transaction information
+    pub fn same_content(&self, other: &PageBuffer) -> bool {
+        let this = self.as_bytes();
+        let other = other.as_bytes();
+
+        this[..P_TRANSACTION_ID] == other[..P_TRANSACTION_ID]
+            && this[P_COL_ID..] == other[P_COL_ID..]
+    }
+
     pub fn col_id(&self) -> u32 {
         self.inner.read_u32(P_COL_ID)
     }
@@ -161,7 +167,7 @@ impl PageBuffer {
         self.inner.write_u8(P_ITEMS_COUNT, items_count);
     }
 
-    fn used_bytes(&self) -> usize {
+    pub fn used_bytes(&self) -> usize {
         self.inner.read_u16(P_USED_BYTES) as usize
     }
 
@@ -170,7 +176,7 @@ impl PageBuffer {
         self.inner.write_u16(P_USED_BYTES, used_bytes as u16);
     }
 
-    fn fragmented_bytes(&self) -> usize {
+    pub fn fragmented_bytes(&self) -> usize {
         self.inner.read_u16(P_FRAGMENTED_BYTES) as usize
     }
 
@@ -180,7 +186,7 @@ impl PageBuffer {
             .write_u16(P_FRAGMENTED_BYTES, fragmented_bytes as u16);
     }
 
-    fn next_free_position(&self) -> usize {
+    pub fn next_free_position(&self) -> usize {
         self.inner.read_u16(P_NEXT_FREE_POSITION) as usize
     }
 
@@ -189,7 +195,7 @@ impl PageBuffer {
             .write_u16(P_NEXT_FREE_POSITION, next_free_position as u16);
     }
 
-    fn highest_index(&self) -> u8 {
+    pub fn highest_index(&self) -> u8 {
         self.inner.read_u8(P_HIGHEST_INDEX)
     }
 
@@ -246,6 +252,27 @@ impl PageBuffer {
         (position, length)
     }
 
+    /// Returns true if all block slots point inside of this page.
+    ///
+    /// Accessing blocks of the page with broken slots panics, so damaged pages must be checked with this.
+    pub fn has_valid_blocks(&self) -> bool {
+        if self.items_count() == 0 {
+            return true;
+        }
+        if self.highest_index() == u8::MAX {
+            return false;
+        }
+
+        (0..=self.highest_index()).all(|index| {
+            let position = self.inner.read_u16(Self::calc_position_addr(index)) as usize;
+            let length = self.inner.read_u16(Self::calc_length_addr(index)) as usize;
+
+            length == 0
+                || (self.valid_position(position, length)
+                    && position + length <= PAGE_SIZE - self.footer_size())
+        })
+    }
+
     pub fn block_exists(&self, index: u8) -> bool {
         self.items_count() > 0
             && index <= self.highest_index()
@@ -320,15 +347,10 @@ impl PageBuffer {
             "continuousBlock must be same as from NextFreePosition"
         );
 
-        assert!(
-            self.fragmented_bytes() == 0,
-            "Our implementation doesn't remove blocks so not fragmented"
-        );
         // if continuous blocks are not enough for this data, must run page defrag
-        //if (bytes_length > continuous_blocks)
-        //{
-        //    this.Defrag();
-        //}
+        if bytes_length > continuous_blocks {
+            self.defrag();
+        }
 
         // if index is new insert segment, must request for new Index
         // get new free index must run after defrag
@@ -381,6 +403,116 @@ impl PageBuffer {
     }
 }
 
+// Block removal
+impl PageBuffer {
+    pub fn delete_block(&mut self, index: u8) {
+        let position_addr = Self::calc_position_addr(index);
+        let length_addr = Self::calc_length_addr(index);
+
+        let position = self.inner.read_u16(position_addr) as usize;
+        let length = self.inner.read_u16(length_addr) as usize;
+
+        debug_assert!(
+            self.valid_position(position, length),
+            "invalid position or length"
+        );
+
+        // clear both position and length
+        self.inner.write_u16(position_addr, 0);
+        self.inner.write_u16(length_addr, 0);
+
+        // add as free blocks
+        self.set_items_count(self.items_count() - 1);
+        self.set_used_bytes(self.used_bytes() - length);
+
+        // clean block area with \0
+        self.inner.as_bytes_mut()[position..][..length].fill(0);
+
+        // check if deleted block is the last block in the page
+        let is_last_segment = position + length == self.next_free_position();
+
+        if is_last_segment {
+            self.set_next_free_position(position);
+        } else {
+            self.set_fragmented_bytes(self.fragmented_bytes() + length);
+        }
+
+        // if deleted if are HighestIndex, update HighestIndex
+        if self.highest_index() == index {
+            self.update_highest_index();
+        }
+
+        // if there is no more blocks in page, clean FragmentedBytes and NextFreePosition
+        if self.items_count() == 0 {
+            debug_assert!(
+                self.used_bytes() == 0,
+                "should be no bytes used in clean page"
+            );
+            self.set_highest_index(u8::MAX);
+            self.set_next_free_position(PAGE_HEADER_SIZE);
+            self.set_fragmented_bytes(0);
+        }
+    }
+
+    fn update_highest_index(&mut self) {
+        for index in (0..self.highest_index()).rev() {
+            if self.inner.read_u16(Self::calc_position_addr(index)) != 0 {
+                self.set_highest_index(index);
+                return;
+            }
+        }
+
+        self.set_highest_index(u8::MAX);
+    }
+
+    /// Moves all blocks to the beginning of the page to make continuous free space
+    fn defrag(&mut self) {
+        debug_assert!(
+            self.fragmented_bytes() > 0,
+            "do not call this when page has no fragmentation"
+        );
+        debug_assert!(
+            self.highest_index() < u8::MAX,
+            "there is no items in this page to run defrag"
+        );
+
+        // first get all blocks inside this page sorted by position (position, index)
+        let mut blocks = (0..=self.highest_index())
+            .map(|index| (self.inner.read_u16(Self::calc_position_addr(index)), index))
+            .filter(|&(position, _)| position != 0)
+            .collect::<Vec<_>>();
+        blocks.sort_unstable();
+
+        // here first block position
+        let mut next = PAGE_HEADER_SIZE;
+
+        // now, list all segments in order to move them to the next available position
+        for (position, index) in blocks {
+            let position = position as usize;
+            let length = self.inner.read_u16(Self::calc_length_addr(index)) as usize;
+
+            // if current segment are not as excpect, copy buffer to right position (excluding empty space)
+            if position != next {
+                self.inner
+                    .as_bytes_mut()
+                    .copy_within(position..position + length, next);
+                self.inner
+                    .write_u16(Self::calc_position_addr(index), next as u16);
+            }
+
+            next += length;
+        }
+
+        // fill all non-used content area with 0
+        let empty_length = PAGE_SIZE - next - self.footer_size();
+        self.inner.as_bytes_mut()[next..][..empty_length].fill(0);
+
+        // clear fragment blocks (page are in a continuous segment)
+        self.set_fragmented_bytes(0);
+        self.set_next_free_position(next);
+    }
+}
+
 impl Deref for PageBuffer {
     type Target = BufferSlice;
 
@@ -1,6 +1,8 @@
 use crate::buffer_reader::BufferReader;
 use crate::constants::{PAGE_FREE_LIST_SLOTS, PAGE_HEADER_SIZE, PAGE_SIZE};
-use crate::utils::{ArenaKey, BufferSlice, CaseInsensitiveString, KeyArena, PageAddress};
+use crate::utils::{
+    ArenaKey, BufferSlice, CaseInsensitiveString, KeyArena, PageAddress, Randomizer,
+};
 use crate::{ParseError, ParseResult, bson};
 use std::collections::HashMap;
 
@@ -33,6 +35,11 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
         return Ok(LiteDBFile::new());
     }
 
+    // password protected file starts with encryption marker instead of page id
+    if data[0] == 1 {
+        return Err(ParseError::encrypted());
+    }
+
     for (index, &page) in pages.iter().enumerate() {
         if index as u32 != page.page_id() {
             return Err(ParseError::invalid_page(index as u32));
@@ -42,7 +49,7 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
         }
     }
 
-    let header = HeaderPage::parse(pages[0])?;
+    let header = HeaderPage::parse(pages[0]).map_err(|e| e.with_page(0))?;
 
     // parse index nodes
     let index_nodes = {
@@ -51,9 +58,10 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
         for &page in pages.iter() {
             if page.page_type() == Some(PageType::Index) {
                 for (index, buffer) in page.blocks() {
+                    let address = PageAddress::new(page.page_id(), index);
                     index_nodes.insert(
-                        PageAddress::new(page.page_id(), index),
-                        RawIndexNode::parse(buffer)?,
+                        address,
+                        RawIndexNode::parse(buffer).map_err(|e| e.with_address(address))?,
                     );
                 }
             }
@@ -103,16 +111,20 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
                     let raw = self
                         .raw_node
                         .remove(&cur)
-                        .ok_or_else(ParseError::bad_reference)?;
+                        .ok_or_else(|| ParseError::bad_reference().with_address(cur))?;
                     buffers.push(raw.buffer());
                     cur = raw.next_block();
                 }
             }
 
             let mut reader = BufferReader::fragmented(buffers);
-            let document = reader.read_document()?;
+            let document = reader
+                .read_document()
+                .map_err(|e| e.with_address(position).with_offset(reader.position()))?;
+            let mut document = DbDocument::new(document);
+            document.position = Some(position);
 
-            Ok(self.arena.alloc(DbDocument::new(document)))
+            Ok(self.arena.alloc(document))
         }
     }
 
@@ -163,26 +175,26 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
                 let raw = self
                     .raw_node
                     .remove(&current)
-                    .ok_or_else(ParseError::bad_reference)?;
+                    .ok_or_else(|| ParseError::bad_reference().with_address(current))?;
 
                 let index_key;
                 let valid;
 
+                let mut index_node = IndexNode::new(raw.slot, raw.levels, raw.key);
+                index_node.position = Some(current);
+
                 if current == index.head || current == index.tail {
                     // head / tail node
-                    let index_node = IndexNode::new(raw.slot, raw.levels, raw.key);
                     index_key = self.arena.alloc(index_node);
                     valid = true;
                 } else {
                     // data node
                     if let Some(data) = get_data_builder(self.data_builder, raw.data_block)? {
-                        let mut index_node = IndexNode::new(raw.slot, raw.levels, raw.key);
                         index_node.data = Some(data);
                         index_key = self.arena.alloc(index_node);
                         self.data_builder.arena[data].index_nodes.push(index_key);
                         valid = true;
                     } else {
-                        let index_node = IndexNode::new(raw.slot, raw.levels, raw.key);
                         index_key = self.arena.alloc(index_node);
                         valid = false;
                     }
@@ -194,18 +206,20 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
                 #[allow(clippy::collapsible_if)]
                 if current == index.head {
                     if !raw.prev.iter().all(PageAddress::is_empty) {
-                        return Err(ParseError::bad_reference());
+                        return Err(ParseError::bad_reference().with_address(current));
                     }
                 } else if current == index.tail {
                     if !raw.next.iter().all(PageAddress::is_empty) {
-                        return Err(ParseError::bad_reference());
+                        return Err(ParseError::bad_reference().with_address(current));
                     }
                 }
 
                 current_addr = Some(raw.next[0]).filter(|x| !x.is_empty());
 
                 // if new current_addr is none, current node must be tail node
-                assert!(current_addr.is_some() || current == index.tail);
+                if current_addr.is_none() && current != index.tail {
+                    return Err(ParseError::bad_reference().with_address(current));
+                }
 
                 address_map.push(RawIndexAddress {
                     key: index_key,
@@ -224,9 +238,9 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
                 if addr.is_empty() {
                     Ok(None)
                 } else {
-                    Ok(Some(
-                        *keys.get(&addr).ok_or_else(ParseError::bad_reference)?,
-                    ))
+                    Ok(Some(*keys.get(&addr).ok_or_else(|| {
+                        ParseError::bad_reference().with_address(addr)
+                    })?))
                 }
             }
 
@@ -280,50 +294,59 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
 
     // parse collection pages
     for (key, page) in header.collections.iter() {
-        let page = page.as_i32().ok_or_else(ParseError::invalid_database)? as u32;
-        let page_buffer = *pages
-            .get(page as usize)
-            .ok_or_else(ParseError::invalid_database)?;
-        let mut collection = RawCollectionPage::parse(page_buffer)?;
-
-        let mut indexes = IndexMap::new();
-
-        let mut data_keys = HashMap::<PageAddress, ArenaKey<DbDocument>>::new();
-
-        {
-            let index = collection
-                .indexes
-                .remove("_id")
-                .ok_or_else(ParseError::no_id_index)?;
-            indexes.insert(
-                "_id".to_string(),
-                index_builder.build(index, |data_builder, data_block| {
-                    let data = data_builder.parse(data_block)?;
-                    data_keys.insert(data_block, data);
-                    Ok(Some(data))
-                })?,
-            );
-        }
+        let mut parse_collection = || -> ParseResult<Collection> {
+            let page = page.as_i32().ok_or_else(ParseError::invalid_database)? as u32;
+            let page_buffer = *pages
+                .get(page as usize)
+                .ok_or_else(|| ParseError::invalid_database().with_page(page))?;
+            let mut collection =
+                RawCollectionPage::parse(page_buffer).map_err(|e| e.with_page(page))?;
 
-        for (name, index) in collection.indexes {
-            if name.as_str() == "_id" {
-                continue;
+            let mut indexes = IndexMap::new();
+
+            let mut data_keys = HashMap::<PageAddress, ArenaKey<DbDocument>>::new();
+
+            {
+                let index = collection
+                    .indexes
+                    .remove("_id")
+                    .ok_or_else(|| ParseError::no_id_index().with_page(page))?;
+                indexes.insert(
+                    "_id".to_string(),
+                    index_builder
+                        .build(index, |data_builder, data_block| {
+                            let data = data_builder.parse(data_block)?;
+                            data_keys.insert(data_block, data);
+                            Ok(Some(data))
+                        })
+                        .map_err(|e| e.with_index("_id"))?,
+                );
             }
 
-            indexes.insert(
-                name.clone(),
-                index_builder.build(index, |_, data_block| {
-                    Ok(data_keys.get(&data_block).cloned())
-                })?,
-            );
-        }
+            for (name, index) in collection.indexes {
+                if name.as_str() == "_id" {
+                    continue;
+                }
 
-        let collection = Collection {
-            indexes,
-            #[cfg(feature = "sequential-index")]
-            last_id: None,
+                indexes.insert(
+                    name.clone(),
+                    index_builder
+                        .build(index, |_, data_block| {
+                            Ok(data_keys.get(&data_block).cloned())
+                        })
+                        .map_err(|e| e.with_index(&name))?,
+                );
+            }
+
+            Ok(Collection {
+                indexes,
+                #[cfg(feature = "sequential-index")]
+                last_id: None,
+                page_id: Some(page),
+            })
         };
 
+        let collection = parse_collection().map_err(|e| e.with_collection(key))?;
         collections.insert(CaseInsensitiveString(key.to_string()), collection);
     }
 
@@ -334,16 +357,23 @@ pub(super) fn parse(data: &[u8]) -> ParseResult<LiteDBFile> {
 
         index_arena: index_builder.arena,
         data: data_builder.arena,
+        loaded_pages: data.into(),
+        last_transaction_id: 0,
+        path: None,
+        keep_backup: false,
+        random: Randomizer::default(),
+        #[cfg(feature = "encryption")]
+        password: None,
     })
 }
 
-mod raw_index_node {
+pub(super) mod raw_index_node {
     use super::*;
 
     use offsets::index_node::*;
 
     #[derive(Debug)]
-    pub(super) struct RawIndexNode {
+    pub(in crate::file_io) struct RawIndexNode {
         pub slot: u8,
         pub levels: u8,
         pub key: bson::Value,
@@ -376,7 +406,9 @@ mod raw_index_node {
             }
 
             let key_ptr = calc_key_ptr(levels);
-            let key = block.read_index_key(key_ptr)?;
+            let key = block
+                .read_index_key(key_ptr)
+                .map_err(|e| e.with_offset(key_ptr))?;
 
             Ok(Self {
                 slot,
@@ -391,13 +423,13 @@ mod raw_index_node {
     }
 }
 
-mod raw_data_block {
+pub(super) mod raw_data_block {
     use super::*;
     use std::fmt::Debug;
 
     use offsets::data_block::*;
 
-    pub(super) struct RawDataBlock<'a> {
+    pub(in crate::file_io) struct RawDataBlock<'a> {
         extend: bool,
         next_block: PageAddress,
         buffer: &'a BufferSlice,
@@ -423,6 +455,11 @@ mod raw_data_block {
         pub fn next_block(&self) -> PageAddress {
             self.next_block
         }
+
+        /// Returns true if this block is not the first block of the document
+        pub fn extend(&self) -> bool {
+            self.extend
+        }
     }
 
     impl Debug for RawDataBlock<'_> {
@@ -436,20 +473,19 @@ mod raw_data_block {
     }
 }
 
-mod header_page {
+pub(super) mod header_page {
     use super::*;
 
     use offsets::header_page::*;
 
     #[derive(Debug)]
-    pub(super) struct HeaderPage {
+    pub(in crate::file_io) struct HeaderPage {
         pub creation_time: bson::DateTime,
         pub pragmas: EnginePragmas,
         pub collections: bson::Document,
         #[allow(dead_code)] // for page structure; not needed for
         last_page_id: u32,
-        #[allow(dead_code)] // for page structure; not needed for
-        free_empty_page_list: u32,
+        pub free_empty_page_list: u32,
     }
 
     impl HeaderPage {
@@ -479,14 +515,13 @@ mod header_page {
     }
 }
 
-mod collection_page {
+pub(super) mod collection_page {
     use super::*;
 
     use offsets::collection_page::*;
 
     #[derive(Debug)]
-    pub(super) struct RawCollectionPage {
-        #[allow(dead_code)]
+    pub(in crate::file_io) struct RawCollectionPage {
         pub free_data_page_list: [u32; 5],
         pub indexes: HashMap<String, RawCollectionIndex>,
     }
@@ -524,7 +559,7 @@ mod collection_page {
     }
 
     #[derive(Debug)]
-    pub(super) struct RawCollectionIndex {
+    pub(in crate::file_io) struct RawCollectionIndex {
         // same as CollectionIndex
         pub slot: u8,
         pub index_type: u8,
@@ -534,13 +569,16 @@ mod collection_page {
         pub reserved: u8,
         pub head: PageAddress,
         pub tail: PageAddress,
-        #[allow(dead_code)] // pages format only
         pub free_index_page_list: u32,
         pub bson_expr: BsonExpression,
     }
 
     impl RawCollectionIndex {
         fn parse(reader: &mut BufferReader) -> ParseResult<Self> {
+            // slot and type
+            if reader.remaining() < 2 {
+                return Err(ParseError::invalid_database());
+            }
             let slot = reader.read_u8();
             let index_type = reader.read_u8();
             let name = reader
@@ -549,6 +587,10 @@ mod collection_page {
             let expression = reader
                 .read_cstring()
                 .ok_or_else(ParseError::invalid_database)?;
+            // unique, head, tail, reserved and free list
+            if reader.remaining() < 1 + PageAddress::SERIALIZED_SIZE * 2 + 1 + 4 {
+                return Err(ParseError::invalid_database());
+            }
             let unique = reader.read_bool();
             let head = reader.read_page_address();
             let tail = reader.read_page_address();
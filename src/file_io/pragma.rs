@@ -1,3 +1,8 @@
+//! The engine pragmas stored in the header page.
+
+use crate::Error;
+use crate::constants::PAGE_SIZE;
+use crate::file_io::LiteDBFile;
 use crate::utils::{BufferSlice, Collation, CompareOptions};
 
 const P_USER_VERSION: usize = 76; // 76-79 (4 bytes)
@@ -9,7 +14,7 @@ const P_UTC_DATE: usize = 96; // 96-96 (1 byte)
 const P_CHECKPOINT: usize = 97; // 97-100 (4 bytes)
 const P_LIMIT_SIZE: usize = 101; // 101-108 (8 bytes)
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub(crate) struct EnginePragmas {
     pub user_version: i32,
     pub collation: Collation,
@@ -64,3 +69,100 @@ impl EnginePragmas {
         buffer.write_i32(P_CHECKPOINT, self.checkpoint);
     }
 }
+
+/// Typed accessors for the pragmas.
+///
+/// Setters validate the value like `PRAGMA` command of LiteDB does.
+impl LiteDBFile {
+    /// Returns `USER_VERSION` pragma, the version number for the schema migration.
+    pub fn user_version(&self) -> i32 {
+        self.pragmas.user_version
+    }
+
+    pub fn set_user_version(&mut self, user_version: i32) {
+        self.pragmas.user_version = user_version;
+    }
+
+    /// Returns `COLLATION` pragma, the collation used to compare strings in indexes.
+    pub fn collation(&self) -> Collation {
+        self.pragmas.collation
+    }
+
+    /// Changes `COLLATION` pragma and rebuilds all indexes under the new ordering.
+    ///
+    /// LiteDB only allows changing collation with rebuild, so this rebuilds indexes like rebuild does.
+    /// If any index cannot be rebuilt, like duplicated keys in unique index under the new collation,
+    /// this returns error and nothing is changed.
+    pub fn set_collation(&mut self, collation: Collation) -> crate::Result<()> {
+        if self.pragmas.collation == collation {
+            return Ok(());
+        }
+        self.rebuild_indexes(collation)
+    }
+
+    /// Returns `TIMEOUT` pragma in seconds.
+    pub fn timeout_seconds(&self) -> i32 {
+        self.pragmas.timeout_seconds
+    }
+
+    /// Sets `TIMEOUT` pragma in seconds. The value must be greater than zero.
+    pub fn set_timeout_seconds(&mut self, timeout_seconds: i32) -> crate::Result<()> {
+        if timeout_seconds <= 0 {
+            return Err(Error::invalid_pragma_value(
+                "Pragma TIMEOUT must be greater than zero".to_string(),
+            ));
+        }
+        self.pragmas.timeout_seconds = timeout_seconds;
+        Ok(())
+    }
+
+    /// Returns `LIMIT_SIZE` pragma, the maximum size of the data file in bytes.
+    pub fn limit_size(&self) -> i64 {
+        self.pragmas.limit_size
+    }
+
+    /// Sets `LIMIT_SIZE` pragma.
+    ///
+    /// The value must be at least 4 pages and not less than the current data file size.
+    pub fn set_limit_size(&mut self, limit_size: i64) -> crate::Result<()> {
+        let min_size = 4 * PAGE_SIZE as i64;
+        if limit_size < min_size {
+            return Err(Error::invalid_pragma_value(format!(
+                "LIMIT_SIZE must be at least {min_size} bytes"
+            )));
+        }
+        let current_size = self.loaded_pages.len() as i64;
+        if limit_size < current_size {
+            return Err(Error::invalid_pragma_value(format!(
+                "LIMIT_SIZE must be at least {current_size} bytes (the current datafile size)"
+            )));
+        }
+        self.pragmas.limit_size = limit_size;
+        Ok(())
+    }
+
+    /// Returns `UTC_DATE` pragma. If false, LiteDB converts dates to local time on read.
+    pub fn utc_date(&self) -> bool {
+        self.pragmas.utc_date
+    }
+
+    pub fn set_utc_date(&mut self, utc_date: bool) {
+        self.pragmas.utc_date = utc_date;
+    }
+
+    /// Returns `CHECKPOINT` pragma, the number of log pages to run checkpoint automatically.
+    pub fn checkpoint(&self) -> i32 {
+        self.pragmas.checkpoint
+    }
+
+    /// Sets `CHECKPOINT` pragma. Zero disables automatic checkpoint.
+    pub fn set_checkpoint(&mut self, checkpoint: i32) -> crate::Result<()> {
+        if checkpoint < 0 {
+            return Err(Error::invalid_pragma_value(
+                "Pragma CHECKPOINT must be greater or equal to zero".to_string(),
+            ));
+        }
+        self.pragmas.checkpoint = checkpoint;
+        Ok(())
+    }
+}
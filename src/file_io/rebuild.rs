@@ -0,0 +1,151 @@
+//! Rebuilding the database like `Rebuild` of LiteDB.
+//!
+//! Rebuilding re-creates all collections and indexes from documents into fresh arenas,
+//! and the next write builds the file from scratch without free space left by removed blocks.
+
+use super::*;
+use crate::Error;
+use crate::utils::Order as InternalOrder;
+
+/// The options for [`LiteDBFile::rebuild`].
+///
+/// `None` fields keep the current settings.
+#[derive(Debug, Clone, Default)]
+pub struct RebuildOptions {
+    /// The new collation.
+    pub collation: Option<Collation>,
+    /// The new password. `Some(None)` removes the password.
+    #[cfg(feature = "encryption")]
+    pub password: Option<Option<String>>,
+    pub user_version: Option<i32>,
+    pub timeout_seconds: Option<i32>,
+    pub limit_size: Option<i64>,
+    pub utc_date: Option<bool>,
+    pub checkpoint: Option<i32>,
+}
+
+/// The result of [`LiteDBFile::rebuild`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebuildReport {
+    /// The size of the database file in bytes before rebuilding.
+    pub size_before: usize,
+    /// The size of the database file in bytes after rebuilding.
+    pub size_after: usize,
+    /// The indexes dropped since they could not be rebuilt, as pairs of collection and index name.
+    pub dropped_indexes: Vec<(String, String)>,
+}
+
+impl LiteDBFile {
+    /// Rebuilds the database with `options`.
+    ///
+    /// All collections and indexes are re-created from documents, and the next
+    /// [`save`](Self::save) or [`serialize`](Self::serialize) writes the compacted file.
+    /// Indexes which cannot be rebuilt, like unique indexes with duplicated keys under the new
+    /// collation, are dropped and reported.
+    ///
+    /// If the options are invalid or documents conflict in `_id` under the new collation,
+    /// this returns an error and the database is not modified.
+    pub fn rebuild(&mut self, options: RebuildOptions) -> crate::Result<RebuildReport> {
+        let size_before = writer::write(self).pages.len();
+
+        let mut rebuilt = LiteDBFile {
+            collections: IndexMap::new(),
+            creation_time: self.creation_time,
+            pragmas: self.pragmas.clone(),
+            index_arena: KeyArena::new(),
+            data: KeyArena::new(),
+            loaded_pages: Arc::new([]),
+            last_transaction_id: self.last_transaction_id,
+            path: self.path.clone(),
+            keep_backup: self.keep_backup,
+            random: self.random.clone(),
+            #[cfg(feature = "encryption")]
+            password: self.password.clone(),
+        };
+
+        if let Some(collation) = options.collation {
+            rebuilt.pragmas.collation = collation;
+        }
+        #[cfg(feature = "encryption")]
+        if let Some(password) = options.password {
+            rebuilt.password = password;
+        }
+        if let Some(user_version) = options.user_version {
+            rebuilt.set_user_version(user_version);
+        }
+        if let Some(timeout_seconds) = options.timeout_seconds {
+            rebuilt.set_timeout_seconds(timeout_seconds)?;
+        }
+        if let Some(limit_size) = options.limit_size {
+            rebuilt.set_limit_size(limit_size)?;
+        }
+        if let Some(utc_date) = options.utc_date {
+            rebuilt.set_utc_date(utc_date);
+        }
+        if let Some(checkpoint) = options.checkpoint {
+            rebuilt.set_checkpoint(checkpoint)?;
+        }
+
+        let mut dropped_indexes = vec![];
+
+        for (name, collection) in &self.collections {
+            let mut new_collection = Collection::new(&mut rebuilt.index_arena);
+
+            let pk_index = collection.pk_index();
+            for pk_key in
+                IndexHelper::find_all(&self.index_arena, pk_index, InternalOrder::Ascending)
+            {
+                let document = &self.data[self.index_arena[pk_key].data.unwrap()];
+
+                Self::insert_document(
+                    &mut rebuilt.index_arena,
+                    &mut rebuilt.data,
+                    &mut rebuilt.random,
+                    rebuilt.pragmas.collation,
+                    &mut new_collection,
+                    document.data.clone(),
+                    BsonAutoId::ObjectId,
+                )?;
+            }
+
+            #[cfg(feature = "sequential-index")]
+            {
+                new_collection.last_id = collection.last_id;
+            }
+
+            rebuilt.collections.insert(name.clone(), new_collection);
+
+            for index in collection.indexes.values() {
+                if index.name == "_id" {
+                    continue;
+                }
+
+                let created = rebuilt
+                    .ensure_index(&name.0, &index.name, index.bson_expr.clone(), index.unique)
+                    .is_ok();
+
+                if !created {
+                    // the index may be partially built
+                    rebuilt.drop_index(&name.0, &index.name)?;
+                    dropped_indexes.push((name.0.clone(), index.name.clone()));
+                }
+            }
+        }
+
+        let size_after = writer::write(&rebuilt).pages.len();
+
+        if size_after as i64 > rebuilt.pragmas.limit_size {
+            return Err(Error::invalid_pragma_value(format!(
+                "LIMIT_SIZE must be at least {size_after} bytes (the rebuilt datafile size)"
+            )));
+        }
+
+        *self = rebuilt;
+
+        Ok(RebuildReport {
+            size_before,
+            size_after,
+            dropped_indexes,
+        })
+    }
+}
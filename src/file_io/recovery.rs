@@ -0,0 +1,342 @@
+//! Recovering documents from damaged database files.
+//!
+//! [`parse`](LiteDBFile::parse) builds the in-memory structure from the index skip lists in the file,
+//! so it fails as soon as one page or reference is broken.
+//! The recovery mode instead collects documents from all readable data pages,
+//! and rebuilds indexes from the recovered documents.
+
+use super::index_helper::IndexHelper;
+use super::offsets::data_block::P_BUFFER;
+use super::page::{PageBuffer, PageType};
+use super::parser::collection_page::{RawCollectionIndex, RawCollectionPage};
+use super::parser::header_page::HeaderPage;
+use super::parser::raw_data_block::RawDataBlock;
+use super::*;
+use crate::buffer_reader::BufferReader;
+use crate::constants::PAGE_SIZE;
+use crate::utils::Order as InternalOrder;
+use crate::{ParseError, ParseResult};
+use std::collections::{HashMap, HashSet};
+
+/// The report of [`LiteDBFile::parse_recovering`] describing what could not be recovered.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RecoveryReport {
+    /// The pages skipped because the page header or the block slots are broken.
+    pub damaged_pages: Vec<u32>,
+    /// The data pages that do not belong to any collection listed in the header page.
+    pub orphan_pages: Vec<u32>,
+    /// The result for each collection listed in the header page.
+    pub collections: Vec<CollectionRecovery>,
+}
+
+impl RecoveryReport {
+    /// Returns true if nothing is lost.
+    pub fn is_clean(&self) -> bool {
+        self.damaged_pages.is_empty()
+            && self.orphan_pages.is_empty()
+            && self.collections.iter().all(CollectionRecovery::is_clean)
+    }
+}
+
+/// The recovery result of a collection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CollectionRecovery {
+    pub name: String,
+    /// The number of recovered documents.
+    pub recovered_documents: usize,
+    /// The number of documents found but not recovered.
+    ///
+    /// This includes documents with broken data block chain, invalid BSON, or duplicated `_id`.
+    /// Documents whose all data blocks are in damaged pages cannot be found, so they are not counted.
+    pub lost_documents: usize,
+    /// True if the collection page is damaged and the index definitions are lost.
+    /// In this case, the collection only has `_id` index.
+    pub lost_index_definitions: bool,
+    /// The names of the indexes which could not be rebuilt.
+    pub lost_indexes: Vec<String>,
+}
+
+impl CollectionRecovery {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            ..Default::default()
+        }
+    }
+
+    /// Returns true if nothing is lost in this collection.
+    pub fn is_clean(&self) -> bool {
+        self.lost_documents == 0 && !self.lost_index_definitions && self.lost_indexes.is_empty()
+    }
+}
+
+impl LiteDBFile {
+    /// Parses the database file, recovering as many documents as possible if the file is damaged.
+    ///
+    /// If the file can be parsed with [`parse`](LiteDBFile::parse), this returns the same database with clean report.
+    /// Otherwise, pages with broken header or block slots are skipped,
+    /// documents are collected from the remaining data pages, and indexes are rebuilt from them.
+    ///
+    /// This still fails if the header page is broken since the collection names are only stored there.
+    ///
+    /// The recovered database does not share pages with `data`, so it should be written as whole file
+    /// with [`serialize`](LiteDBFile::serialize) or [`save`](LiteDBFile::save),
+    /// not with [`serialize_log`](LiteDBFile::serialize_log).
+    pub fn parse_recovering(data: &[u8]) -> ParseResult<(Self, RecoveryReport)> {
+        parse_recovering(data)
+    }
+}
+
+pub(super) fn parse_recovering(data: &[u8]) -> ParseResult<(LiteDBFile, RecoveryReport)> {
+    if let Ok(file) = parser::parse(data) {
+        let report = RecoveryReport {
+            collections: file
+                .collections
+                .keys()
+                .map(|name| CollectionRecovery {
+                    recovered_documents: file.get_all(&name.0).count(),
+                    ..CollectionRecovery::new(name.0.clone())
+                })
+                .collect(),
+            ..Default::default()
+        };
+        return Ok((file, report));
+    }
+
+    // parse succeeds for empty data so we have at least one page here
+    let data = &data[..(data.len() & !(PAGE_SIZE - 1))];
+
+    if data[0] == 1 {
+        return Err(ParseError::encrypted());
+    }
+
+    let pages = data
+        .chunks(PAGE_SIZE)
+        .map(PageBuffer::new)
+        .collect::<Vec<_>>();
+
+    let header = HeaderPage::parse(pages[0])?;
+
+    let mut report = RecoveryReport::default();
+
+    let readable = pages
+        .iter()
+        .enumerate()
+        .map(|(index, &page)| index == 0 || is_readable_page(index as u32, page))
+        .collect::<Vec<_>>();
+
+    report.damaged_pages = readable
+        .iter()
+        .enumerate()
+        .filter(|&(_, &readable)| !readable)
+        .map(|(index, _)| index as u32)
+        .collect();
+
+    let mut file = LiteDBFile::new();
+    file.creation_time = header.creation_time;
+    file.pragmas = header.pragmas;
+
+    // collection page id -> index in report.collections
+    let mut collection_pages = HashMap::<u32, usize>::new();
+    let mut index_definitions = Vec::<Vec<RawCollectionIndex>>::new();
+
+    for (name, page) in header.collections.iter() {
+        let name = CaseInsensitiveString(name.to_string());
+        if file.collections.contains_key(&name) {
+            continue;
+        }
+
+        let mut recovery = CollectionRecovery::new(name.0.clone());
+
+        let collection_page = page.as_i32().map(|page| page as u32);
+        if let Some(page) = collection_page {
+            collection_pages.insert(page, report.collections.len());
+        }
+
+        let mut indexes = collection_page
+            .filter(|&page| readable.get(page as usize) == Some(&true))
+            .and_then(|page| RawCollectionPage::parse(pages[page as usize]).ok())
+            .map(|collection| collection.indexes.into_values().collect::<Vec<_>>())
+            .unwrap_or_else(|| {
+                recovery.lost_index_definitions = true;
+                Vec::new()
+            });
+        indexes.sort_by_key(|index| index.slot);
+        index_definitions.push(indexes);
+
+        file.collections
+            .insert(name, Collection::new(&mut file.index_arena));
+        report.collections.push(recovery);
+    }
+
+    // collect data blocks
+    let mut blocks = HashMap::<PageAddress, (u32, RawDataBlock)>::new();
+    let mut first_blocks = Vec::<PageAddress>::new();
+
+    for (index, &page) in pages.iter().enumerate() {
+        if !readable[index] || page.page_type() != Some(PageType::Data) {
+            continue;
+        }
+
+        if !collection_pages.contains_key(&page.col_id()) {
+            report.orphan_pages.push(index as u32);
+            continue;
+        }
+
+        for (slot, buffer) in page.blocks() {
+            let address = PageAddress::new(index as u32, slot);
+            let block = RawDataBlock::parse(buffer);
+            if !block.extend() {
+                first_blocks.push(address);
+            }
+            blocks.insert(address, (page.col_id(), block));
+        }
+    }
+
+    // recover documents
+    let mut visited = HashSet::<PageAddress>::new();
+
+    for first_block in first_blocks {
+        let col_id = blocks[&first_block].0;
+        let collection_index = collection_pages[&col_id];
+
+        let recovered = read_document(&blocks, &mut visited, first_block)
+            .is_some_and(|document| insert_document(&mut file, collection_index, document));
+
+        let recovery = &mut report.collections[collection_index];
+        if recovered {
+            recovery.recovered_documents += 1;
+        } else {
+            recovery.lost_documents += 1;
+        }
+    }
+
+    // the rest blocks are fragments of documents whose first block is lost.
+    // count the first fragment of each document as lost document
+    let referenced = blocks
+        .iter()
+        .filter(|(address, _)| !visited.contains(address))
+        .map(|(_, (_, block))| block.next_block())
+        .collect::<HashSet<_>>();
+
+    for (address, (col_id, _)) in &blocks {
+        if !visited.contains(address) && !referenced.contains(address) {
+            report.collections[collection_pages[col_id]].lost_documents += 1;
+        }
+    }
+
+    // rebuild indexes
+    for (collection_index, indexes) in index_definitions.into_iter().enumerate() {
+        let recovery = &mut report.collections[collection_index];
+
+        for index in indexes {
+            if index.name == "_id" {
+                continue;
+            }
+
+            let result =
+                file.ensure_index(&recovery.name, &index.name, index.bson_expr, index.unique);
+
+            if result.is_err() {
+                // the index may be partially built
+                file.drop_index(&recovery.name, &index.name).ok();
+                recovery.lost_indexes.push(index.name);
+            }
+        }
+    }
+
+    Ok((file, report))
+}
+
+fn is_readable_page(page_id: u32, page: &PageBuffer) -> bool {
+    if page.page_id() != page_id || page.page_type().is_none() || !page.has_valid_blocks() {
+        return false;
+    }
+
+    if page.page_type() == Some(PageType::Data) {
+        // data block should have data block header
+        return page.blocks().all(|(_, block)| block.len() >= P_BUFFER);
+    }
+
+    true
+}
+
+/// Reads the document from the chain of data blocks starting with `first_block`
+fn read_document(
+    blocks: &HashMap<PageAddress, (u32, RawDataBlock)>,
+    visited: &mut HashSet<PageAddress>,
+    first_block: PageAddress,
+) -> Option<bson::Document> {
+    let col_id = blocks[&first_block].0;
+    let mut buffers = vec![];
+
+    let mut cur = first_block;
+    while !cur.is_empty() {
+        let (block_col_id, block) = blocks.get(&cur)?;
+        // the chain must not point to other documents
+        if *block_col_id != col_id || (cur != first_block && !block.extend()) {
+            return None;
+        }
+        // the loop in the chain or block shared with other documents
+        if !visited.insert(cur) {
+            return None;
+        }
+        buffers.push(block.buffer());
+        cur = block.next_block();
+    }
+
+    BufferReader::fragmented(buffers).read_document().ok()
+}
+
+/// Adds recovered document to `_id` index of the collection.
+///
+/// Returns false if the document cannot be added since the `_id` is invalid or duplicated.
+fn insert_document(
+    file: &mut LiteDBFile,
+    collection_index: usize,
+    document: bson::Document,
+) -> bool {
+    let Some(id) = document.try_get("_id").cloned() else {
+        return false;
+    };
+    if matches!(
+        id,
+        bson::Value::Null | bson::Value::MinValue | bson::Value::MaxValue
+    ) {
+        return false;
+    }
+
+    let (_, collection) = file.collections.get_index(collection_index).unwrap();
+    let pk_index = collection.pk_index();
+
+    // IndexHelper::add_node may leave the node partially linked on duplicated key, so check before adding
+    let collation = file.pragmas.collation;
+    if IndexHelper::find(
+        &file.index_arena,
+        &collation,
+        pk_index,
+        &id,
+        false,
+        InternalOrder::Ascending,
+    )
+    .is_some()
+    {
+        return false;
+    }
+
+    let data_key = file.data.alloc(DbDocument::new(document));
+    let result = IndexHelper::add_node(
+        &mut file.index_arena,
+        &mut file.data,
+        &mut file.random,
+        &collation,
+        pk_index,
+        id,
+        data_key,
+    );
+
+    if result.is_err() {
+        file.data.free(data_key);
+    }
+    result.is_ok()
+}
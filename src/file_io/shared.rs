@@ -0,0 +1,81 @@
+//! The database handle shared between threads.
+//!
+//! Like LiteDB, [`SharedLiteDB`] allows multiple readers and a single writer at the same time.
+//! Readers get the snapshot of the database at the time they start reading, and changes made by
+//! the writer are published to readers at once when the write completes.
+
+use super::*;
+use std::sync::{Arc, RwLock};
+
+/// The thread-safe handle of [`LiteDBFile`] with multiple readers and a single writer.
+///
+/// Readers never wait for the writer, and the writer never invalidates the snapshot readers hold,
+/// so iterators returned from the snapshot (like [`LiteDBFile::get_all`]) are valid while the
+/// snapshot is alive.
+pub struct SharedLiteDB {
+    /// The latest published state of the database
+    current: RwLock<Arc<LiteDBFile>>,
+    /// The lock to ensure single writer
+    writer: async_lock::Mutex<()>,
+}
+
+impl SharedLiteDB {
+    pub fn new(file: LiteDBFile) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(file)),
+            writer: async_lock::Mutex::new(()),
+        }
+    }
+
+    /// Returns the snapshot of the latest published state.
+    ///
+    /// The snapshot is not affected by writes after this call.
+    pub fn read(&self) -> Arc<LiteDBFile> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Runs `f` with exclusive write access to the database, blocking until other writers complete.
+    ///
+    /// `f` modifies a [snapshot](LiteDBFile::snapshot) of the latest state, and the snapshot is
+    /// published to readers after `f` returns.
+    /// Taking the snapshot only copies pointers, and documents and indexes are copied when modified.
+    /// If `f` panics, nothing is published.
+    /// To discard changes on error, use [`LiteDBFile::atomically`] in `f`.
+    pub fn write<T>(&self, f: impl FnOnce(&mut LiteDBFile) -> T) -> T {
+        let _guard = self.writer.lock_blocking();
+        self.write_locked(f)
+    }
+
+    /// Async version of [`write`](Self::write), which waits for other writers asynchronously.
+    pub async fn write_async<T>(&self, f: impl FnOnce(&mut LiteDBFile) -> T) -> T {
+        let _guard = self.writer.lock().await;
+        self.write_locked(f)
+    }
+
+    fn write_locked<T>(&self, f: impl FnOnce(&mut LiteDBFile) -> T) -> T {
+        let mut file = self.read().snapshot();
+        let result = f(&mut file);
+        *self.current.write().unwrap() = Arc::new(file);
+        result
+    }
+
+    /// Returns the latest state of the database.
+    pub fn into_inner(self) -> LiteDBFile {
+        let file = self.current.into_inner().unwrap();
+        Arc::try_unwrap(file).unwrap_or_else(|file| LiteDBFile::clone(&file))
+    }
+}
+
+impl From<LiteDBFile> for SharedLiteDB {
+    fn from(file: LiteDBFile) -> Self {
+        Self::new(file)
+    }
+}
+
+#[allow(dead_code)]
+fn _type_check() {
+    use crate::utils::checker::*;
+
+    check_sync_send(dummy::<SharedLiteDB>());
+    check_sync_send(dummy::<Arc<LiteDBFile>>());
+}
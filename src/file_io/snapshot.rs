@@ -0,0 +1,44 @@
+//! Snapshots of [`LiteDBFile`].
+//!
+//! Documents and index nodes are stored in copy-on-write arenas, and loaded pages are shared,
+//! so cloning [`LiteDBFile`] only copies collection definitions and pointers to shared chunks.
+
+use super::*;
+
+impl LiteDBFile {
+    /// Takes the snapshot of the current state.
+    ///
+    /// This is cheap since the snapshot shares documents and indexes with this database until
+    /// either of them is modified.
+    /// The snapshot can be queried and serialized like other [`LiteDBFile`]s,
+    /// and can be restored with [`restore`](Self::restore).
+    pub fn snapshot(&self) -> LiteDBFile {
+        self.clone()
+    }
+
+    /// Restores the collections, documents, indexes and pragmas from the snapshot.
+    ///
+    /// The path and the state of the file on disk are kept, so saving after restoring
+    /// writes the restored state to the file this database is opened from.
+    pub fn restore(&mut self, snapshot: LiteDBFile) {
+        self.collections = snapshot.collections;
+        self.creation_time = snapshot.creation_time;
+        self.pragmas = snapshot.pragmas;
+        self.index_arena = snapshot.index_arena;
+        self.data = snapshot.data;
+
+        if !Arc::ptr_eq(&self.loaded_pages, &snapshot.loaded_pages) {
+            // the file is written after the snapshot is taken so positions in the snapshot
+            // do not match to the loaded pages. all blocks will be written again
+            for collection in self.collections.values_mut() {
+                collection.page_id = None;
+            }
+            for node in self.index_arena.values_mut() {
+                node.position = None;
+            }
+            for document in self.data.values_mut() {
+                document.position = None;
+            }
+        }
+    }
+}
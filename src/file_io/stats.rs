@@ -0,0 +1,161 @@
+//! Storage statistics of the database.
+
+use super::page::{PageBuffer, PageType};
+use super::*;
+use crate::constants::PAGE_SIZE;
+
+/// The statistics returned by [`LiteDBFile::stats`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatabaseStats {
+    pub collections: Vec<CollectionStats>,
+    /// The page layout of the file as loaded or last written.
+    /// `None` if this database is neither loaded from nor written to a file.
+    pub pages: Option<PageStats>,
+}
+
+/// The statistics of a collection.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollectionStats {
+    pub name: String,
+    /// The number of documents.
+    pub documents: usize,
+    /// The total size of documents serialized as BSON.
+    pub document_bytes: usize,
+    pub indexes: Vec<IndexStats>,
+    /// The number of data pages of this collection in the file.
+    /// `None` if the page layout is not available or the collection is not written yet.
+    pub data_pages: Option<usize>,
+    /// The number of index pages of this collection in the file.
+    /// `None` if the page layout is not available or the collection is not written yet.
+    pub index_pages: Option<usize>,
+}
+
+/// The statistics of an index.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexStats {
+    pub name: String,
+    /// The number of index nodes, excluding head and tail nodes.
+    pub nodes: usize,
+    /// The average number of skip list levels of nodes. Zero if there are no nodes.
+    pub average_levels: f64,
+}
+
+/// The page layout of the database file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageStats {
+    pub total_pages: usize,
+    pub collection_pages: usize,
+    pub data_pages: usize,
+    pub index_pages: usize,
+    pub empty_pages: usize,
+    /// The free bytes of each page, indexed by the page id.
+    /// Header and collection pages are reported as zero.
+    pub free_bytes: Vec<usize>,
+}
+
+impl LiteDBFile {
+    /// Returns the statistics of collections, indexes and pages.
+    ///
+    /// Document and index statistics reflect the current state, while page statistics reflect
+    /// the file as it was loaded or last written with [`save`](Self::save) or
+    /// [`serialize_log`](Self::serialize_log).
+    pub fn stats(&self) -> DatabaseStats {
+        let pages = self
+            .loaded_pages
+            .chunks_exact(PAGE_SIZE)
+            .map(PageBuffer::new)
+            .collect::<Vec<_>>();
+
+        let collections = self
+            .collections
+            .iter()
+            .map(|(name, collection)| {
+                let pk_nodes = IndexHelper::find_all(
+                    &self.index_arena,
+                    collection.pk_index(),
+                    InternalOrder::Ascending,
+                );
+
+                let document_bytes = pk_nodes
+                    .iter()
+                    .map(|&x| &self.data[self.index_arena[x].data.unwrap()])
+                    .map(|x| x.data.get_serialized_value_len())
+                    .sum();
+
+                let indexes = collection
+                    .indexes
+                    .values()
+                    .map(|index| {
+                        let nodes = IndexHelper::find_all(
+                            &self.index_arena,
+                            index,
+                            InternalOrder::Ascending,
+                        );
+                        let levels = nodes
+                            .iter()
+                            .map(|&x| self.index_arena[x].levels as usize)
+                            .sum::<usize>();
+
+                        IndexStats {
+                            name: index.name.clone(),
+                            nodes: nodes.len(),
+                            average_levels: if nodes.is_empty() {
+                                0.0
+                            } else {
+                                levels as f64 / nodes.len() as f64
+                            },
+                        }
+                    })
+                    .collect();
+
+                let count_pages = |page_type: PageType| {
+                    let col_id = collection.page_id.filter(|_| !pages.is_empty())?;
+                    Some(
+                        pages
+                            .iter()
+                            .filter(|page| page.page_type() == Some(page_type))
+                            .filter(|page| page.col_id() == col_id)
+                            .count(),
+                    )
+                };
+
+                CollectionStats {
+                    name: name.0.clone(),
+                    documents: pk_nodes.len(),
+                    document_bytes,
+                    indexes,
+                    data_pages: count_pages(PageType::Data),
+                    index_pages: count_pages(PageType::Index),
+                }
+            })
+            .collect();
+
+        let pages = (!pages.is_empty()).then(|| {
+            let count = |page_type: PageType| {
+                pages
+                    .iter()
+                    .filter(|page| page.page_type() == Some(page_type))
+                    .count()
+            };
+
+            PageStats {
+                total_pages: pages.len(),
+                collection_pages: count(PageType::Collection),
+                data_pages: count(PageType::Data),
+                index_pages: count(PageType::Index),
+                empty_pages: count(PageType::Empty),
+                free_bytes: pages
+                    .iter()
+                    .map(|page| match page.page_type() {
+                        Some(PageType::Data | PageType::Index | PageType::Empty) => {
+                            page.free_bytes()
+                        }
+                        _ => 0,
+                    })
+                    .collect(),
+            }
+        });
+
+        DatabaseStats { collections, pages }
+    }
+}
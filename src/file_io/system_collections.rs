@@ -0,0 +1,187 @@
+//! The read-only system collections like `$cols` or `$database`.
+//!
+//! The documents have the same shape as the system collections of LiteDB.
+//! They are built from the current state on each query, and cannot be modified.
+
+use super::page::{PageBuffer, PageType};
+use super::parser::header_page::HeaderPage;
+use super::*;
+use crate::constants::PAGE_SIZE;
+use crate::utils::CaseInsensitiveStr;
+use std::collections::HashMap;
+
+/// The names of the system collections
+const SYSTEM_COLLECTIONS: &[&str] = &["$cols", "$database", "$dump", "$indexes", "$page_list"];
+
+impl LiteDBFile {
+    /// Returns the documents of the system collection like `$cols` or `$database`.
+    ///
+    /// The documents are built from the current state of the database.
+    /// Returns `None` if `name` is not a system collection.
+    pub fn system_collection(&self, name: &str) -> Option<Vec<bson::Document>> {
+        let name = SYSTEM_COLLECTIONS
+            .iter()
+            .find(|&&x| CaseInsensitiveStr::new(x) == CaseInsensitiveStr::new(name))?;
+
+        Some(match *name {
+            "$cols" => self.sys_cols(),
+            "$database" => vec![self.sys_database()],
+            "$dump" => self.sys_pages(true),
+            "$indexes" => self.sys_indexes(),
+            "$page_list" => self.sys_pages(false),
+            _ => unreachable!(),
+        })
+    }
+
+    fn sys_cols(&self) -> Vec<bson::Document> {
+        let user = self.collections.keys().map(|name| {
+            document! {
+                "name" => &name.0,
+                "type" => "user",
+            }
+        });
+        let system = SYSTEM_COLLECTIONS.iter().map(|&name| {
+            document! {
+                "name" => name,
+                "type" => "system",
+            }
+        });
+
+        user.chain(system).collect()
+    }
+
+    fn sys_indexes(&self) -> Vec<bson::Document> {
+        self.collections
+            .keys()
+            .flat_map(|name| self.indexes(&name.0))
+            .map(|index| {
+                document! {
+                    "collection" => index.collection,
+                    "slot" => index.slot as i32,
+                    "idxType" => index.index_type as i32,
+                    "name" => index.name,
+                    "expression" => index.expression,
+                    "unique" => index.unique,
+                    "maxLevel" => index.max_level as i32,
+                }
+            })
+            .collect()
+    }
+
+    fn sys_database(&self) -> bson::Document {
+        let pages = writer::write(self).pages;
+        let header = HeaderPage::parse(PageBuffer::new(&pages[..PAGE_SIZE]))
+            .expect("written header page is valid");
+
+        #[cfg(feature = "encryption")]
+        let encrypted = self.password.is_some();
+        #[cfg(not(feature = "encryption"))]
+        let encrypted = false;
+
+        // the encrypted file has the hidden page for salt
+        let file_size = pages.len() + if encrypted { PAGE_SIZE } else { 0 };
+
+        let name = self
+            .path
+            .as_ref()
+            .and_then(|x| x.file_name())
+            .map(|x| x.to_string_lossy().into_owned());
+
+        let pragmas = &self.pragmas;
+
+        document! {
+            "name" => name,
+            "encrypted" => encrypted,
+            "readOnly" => false,
+            "lastPageID" => (pages.len() / PAGE_SIZE - 1) as i32,
+            "freeEmptyPageID" => page_id_value(header.free_empty_page_list),
+            "creationTime" => self.creation_time,
+            "dataFileSize" => file_size as i32,
+            // we always checkpoint the log file on save
+            "logFileSize" => 0,
+            "lastTransactionID" => self.last_transaction_id as i32,
+            "engine" => concat!("litedb-rs-v", env!("CARGO_PKG_VERSION")),
+            "pragmas" => document! {
+                "USER_VERSION" => pragmas.user_version,
+                "COLLATION" => pragmas.collation.to_string(),
+                "TIMEOUT" => pragmas.timeout_seconds,
+                "LIMIT_SIZE" => pragmas.limit_size,
+                "UTC_DATE" => pragmas.utc_date,
+                "CHECKPOINT" => pragmas.checkpoint,
+            },
+        }
+    }
+
+    /// Returns the documents of `$page_list`, or `$dump` if `dump` is true.
+    fn sys_pages(&self, dump: bool) -> Vec<bson::Document> {
+        let pages = writer::write(self).pages;
+        let header = HeaderPage::parse(PageBuffer::new(&pages[..PAGE_SIZE]))
+            .expect("written header page is valid");
+
+        let collections = header
+            .collections
+            .iter()
+            .filter_map(|(name, page_id)| Some((page_id.as_i32()? as u32, name)))
+            .collect::<HashMap<_, _>>();
+
+        pages
+            .chunks_exact(PAGE_SIZE)
+            .map(PageBuffer::new)
+            .map(|page| {
+                let page_type = match page.page_type() {
+                    Some(PageType::Empty) => "Empty",
+                    Some(PageType::Header) => "Header",
+                    Some(PageType::Collection) => "Collection",
+                    Some(PageType::Index) => "Index",
+                    Some(PageType::Data) => "Data",
+                    None => "Unknown",
+                };
+
+                let collection = match page.page_type() {
+                    Some(PageType::Empty | PageType::Header) | None => None,
+                    Some(_) => collections.get(&page.col_id()),
+                }
+                .map_or("-", |&x| x);
+
+                if !dump {
+                    return document! {
+                        "pageID" => page.page_id() as i32,
+                        "pageType" => page_type,
+                        "slot" => page.page_list_slot() as i32,
+                        "collection" => collection,
+                        "itemsCount" => page.items_count() as i32,
+                        "freeBytes" => page.free_bytes() as i32,
+                    };
+                }
+
+                document! {
+                    "_position" => page.page_id() as i64 * PAGE_SIZE as i64,
+                    "_origin" => "data",
+                    "pageID" => page.page_id() as i32,
+                    "pageType" => page_type,
+                    "nextPageID" => page_id_value(page.next_page_id()),
+                    "prevPageID" => page_id_value(page.prev_page_id()),
+                    "collection" => collection,
+                    "transactionID" => page.transaction_id() as i32,
+                    "isConfirmed" => page.is_confirmed(),
+                    "itemsCount" => page.items_count() as i32,
+                    "freeBytes" => page.free_bytes() as i32,
+                    "usedBytes" => page.used_bytes() as i32,
+                    "fragmentedBytes" => page.fragmented_bytes() as i32,
+                    "nextFreePosition" => page.next_free_position() as i32,
+                    "highestIndex" => page.highest_index() as i32,
+                    "buffer" => bson::Binary::new(page.as_bytes().to_vec()),
+                }
+            })
+            .collect()
+    }
+}
+
+/// LiteDB shows `uint.MaxValue` page id as null
+fn page_id_value(page_id: u32) -> bson::Value {
+    if page_id == u32::MAX {
+        bson::Value::Null
+    } else {
+        bson::Value::Int32(page_id as i32)
+    }
+}
@@ -0,0 +1,262 @@
+//! Transactions on [`LiteDBFile`].
+//!
+//! Since [`LiteDBFile`] is an in-memory database, a transaction is implemented by keeping
+//! the state of collections, indexes and documents at the beginning of the transaction,
+//! and restoring it on rollback.
+
+use super::pragma::EnginePragmas;
+use super::*;
+use crate::file_io::operations::RenameCollectionResult;
+
+/// The state of [`LiteDBFile`] modified by operations.
+///
+/// The loaded pages and other state for writing files are not included since they're not
+/// modified by operations on [`Transaction`].
+struct State {
+    collections: IndexMap<CaseInsensitiveString, Collection>,
+    creation_time: bson::DateTime,
+    pragmas: EnginePragmas,
+    index_arena: KeyArena<IndexNode>,
+    data: KeyArena<DbDocument>,
+    random: Randomizer,
+    #[cfg(feature = "encryption")]
+    password: Option<String>,
+}
+
+impl LiteDBFile {
+    /// Begins a transaction.
+    ///
+    /// Changes made through the returned [`Transaction`] are kept only if
+    /// [`Transaction::commit`] is called.
+    /// Dropping the transaction or calling [`Transaction::rollback`] restores
+    /// the state at the beginning of the transaction.
+    pub fn begin_transaction(&mut self) -> Transaction<'_> {
+        let state = State {
+            collections: self.collections.clone(),
+            creation_time: self.creation_time,
+            pragmas: self.pragmas.clone(),
+            index_arena: self.index_arena.clone(),
+            data: self.data.clone(),
+            random: self.random.clone(),
+            #[cfg(feature = "encryption")]
+            password: self.password.clone(),
+        };
+
+        Transaction {
+            file: self,
+            state: Some(state),
+        }
+    }
+
+    /// Runs `f` in a transaction.
+    ///
+    /// The transaction is committed if `f` returns `Ok`, and rolled back if `f` returns `Err`.
+    /// This can be used to make a batch of operations all-or-nothing, including a batch of documents
+    /// passed to [`insert`](LiteDBFile::insert), [`update`](LiteDBFile::update)
+    /// or [`upsert`](LiteDBFile::upsert).
+    pub fn atomically<T, E>(
+        &mut self,
+        f: impl FnOnce(&mut Transaction<'_>) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let mut transaction = self.begin_transaction();
+        let result = f(&mut transaction)?;
+        transaction.commit();
+        Ok(result)
+    }
+}
+
+/// The transaction on [`LiteDBFile`] created by [`LiteDBFile::begin_transaction`].
+///
+/// Documents and settings can be queried through the transaction, including uncommitted changes.
+/// Serializing or saving the file is not possible while the transaction is active,
+/// since uncommitted changes would be written.
+pub struct Transaction<'a> {
+    file: &'a mut LiteDBFile,
+    /// The state at the beginning of the transaction. `None` if committed.
+    state: Option<State>,
+}
+
+impl Transaction<'_> {
+    /// Commits the transaction and keeps the changes.
+    pub fn commit(mut self) {
+        self.state = None;
+    }
+
+    /// Rolls back the transaction and restores the state at the beginning of the transaction.
+    ///
+    /// This is same as dropping the transaction.
+    pub fn rollback(self) {}
+}
+
+impl Drop for Transaction<'_> {
+    fn drop(&mut self) {
+        if let Some(state) = self.state.take() {
+            self.file.collections = state.collections;
+            self.file.creation_time = state.creation_time;
+            self.file.pragmas = state.pragmas;
+            self.file.index_arena = state.index_arena;
+            self.file.data = state.data;
+            self.file.random = state.random;
+            #[cfg(feature = "encryption")]
+            {
+                self.file.password = state.password;
+            }
+        }
+    }
+}
+
+/// Operations querying the database. See the methods of [`LiteDBFile`] for details.
+impl Transaction<'_> {
+    pub fn get_collection_names(&self) -> Vec<String> {
+        self.file.get_collection_names()
+    }
+
+    pub fn indexes(&self, collection: &str) -> Vec<IndexInfo> {
+        self.file.indexes(collection)
+    }
+
+    pub fn get_all(&self, collection: &str) -> impl Iterator<Item = &bson::Document> {
+        self.file.get_all(collection)
+    }
+
+    pub fn get_range_indexed(
+        &self,
+        collection: &str,
+        index: &str,
+        min_inclusive: &bson::Value,
+        max_inclusive: &bson::Value,
+        order: Order,
+    ) -> impl Iterator<Item = &bson::Document> {
+        self.file
+            .get_range_indexed(collection, index, min_inclusive, max_inclusive, order)
+    }
+
+    pub fn get_by_index(
+        &self,
+        collection: &str,
+        index: &str,
+        find: &bson::Value,
+    ) -> impl Iterator<Item = &bson::Document> {
+        self.file.get_by_index(collection, index, find)
+    }
+
+    pub fn creation_time(&self) -> bson::DateTime {
+        self.file.creation_time()
+    }
+
+    pub fn user_version(&self) -> i32 {
+        self.file.user_version()
+    }
+
+    pub fn collation(&self) -> Collation {
+        self.file.collation()
+    }
+
+    pub fn timeout_seconds(&self) -> i32 {
+        self.file.timeout_seconds()
+    }
+
+    pub fn limit_size(&self) -> i64 {
+        self.file.limit_size()
+    }
+
+    pub fn utc_date(&self) -> bool {
+        self.file.utc_date()
+    }
+
+    pub fn checkpoint(&self) -> i32 {
+        self.file.checkpoint()
+    }
+}
+
+/// Operations modifying the database. See the methods of [`LiteDBFile`] for details.
+impl Transaction<'_> {
+    pub fn insert(
+        &mut self,
+        collection: &str,
+        docs: Vec<bson::Document>,
+        auto_id: BsonAutoId,
+    ) -> crate::Result<usize> {
+        self.file.insert(collection, docs, auto_id)
+    }
+
+    pub fn update(&mut self, collection: &str, docs: Vec<bson::Document>) -> crate::Result<usize> {
+        self.file.update(collection, docs)
+    }
+
+    pub fn upsert(
+        &mut self,
+        collection: &str,
+        docs: Vec<bson::Document>,
+        auto_id: BsonAutoId,
+    ) -> crate::Result<usize> {
+        self.file.upsert(collection, docs, auto_id)
+    }
+
+    pub fn delete(&mut self, collection: &str, ids: &[bson::Value]) -> usize {
+        self.file.delete(collection, ids)
+    }
+
+    pub fn ensure_index(
+        &mut self,
+        collection: &str,
+        name: &str,
+        expression: BsonExpression,
+        unique: bool,
+    ) -> crate::Result<bool> {
+        self.file.ensure_index(collection, name, expression, unique)
+    }
+
+    pub fn drop_index(&mut self, collection: &str, name: &str) -> crate::Result<bool> {
+        self.file.drop_index(collection, name)
+    }
+
+    pub fn drop_collection(&mut self, name: &str) -> bool {
+        self.file.drop_collection(name)
+    }
+
+    pub fn rename_collection(
+        &mut self,
+        old_name: &str,
+        new_name: &str,
+    ) -> crate::Result<RenameCollectionResult> {
+        self.file.rename_collection(old_name, new_name)
+    }
+
+    pub fn set_user_version(&mut self, user_version: i32) {
+        self.file.set_user_version(user_version)
+    }
+
+    pub fn set_collation(&mut self, collation: Collation) -> crate::Result<()> {
+        self.file.set_collation(collation)
+    }
+
+    pub fn set_timeout_seconds(&mut self, timeout_seconds: i32) -> crate::Result<()> {
+        self.file.set_timeout_seconds(timeout_seconds)
+    }
+
+    pub fn set_limit_size(&mut self, limit_size: i64) -> crate::Result<()> {
+        self.file.set_limit_size(limit_size)
+    }
+
+    pub fn set_utc_date(&mut self, utc_date: bool) {
+        self.file.set_utc_date(utc_date)
+    }
+
+    pub fn set_checkpoint(&mut self, checkpoint: i32) -> crate::Result<()> {
+        self.file.set_checkpoint(checkpoint)
+    }
+
+    pub fn set_creation_time(&mut self, creation_time: bson::DateTime) {
+        self.file.set_creation_time(creation_time)
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn set_password(&mut self, password: Option<&str>) {
+        self.file.set_password(password)
+    }
+
+    pub fn rebuild(&mut self, options: RebuildOptions) -> crate::Result<RebuildReport> {
+        self.file.rebuild(options)
+    }
+}
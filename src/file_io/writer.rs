@@ -8,37 +8,125 @@ use crate::constants::{
 use crate::file_io::index_helper::IndexHelper;
 use crate::file_io::offsets::collection_page::P_INDEXES;
 use crate::file_io::page::{PageBuffer, PageType};
+use crate::file_io::parser::collection_page::RawCollectionPage;
 use crate::utils::{BufferSlice, PageAddress};
 use itertools::Itertools;
 use std::cmp::min;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::ops::{Index, IndexMut};
 
 impl LiteDBFile {
     pub fn serialize(&self) -> Vec<u8> {
-        write(self)
+        self.file_content(write(self).pages)
+    }
+
+    /// Converts the pages to the content of the file.
+    ///
+    /// If the password is set, this encrypts the pages.
+    pub(super) fn file_content(&self, pages: Vec<u8>) -> Vec<u8> {
+        self.encrypted_content(&pages).unwrap_or(pages)
+    }
+
+    /// Returns the encrypted content of the file if the password is set.
+    ///
+    /// Otherwise, the pages are the content of the file as is.
+    pub(super) fn encrypted_content(&self, pages: &[u8]) -> Option<Vec<u8>> {
+        #[cfg(feature = "encryption")]
+        if let Some(password) = &self.password {
+            return Some(super::encryption::encrypt(
+                pages,
+                password,
+                self.random.peek_bytes(),
+            ));
+        }
+
+        let _ = pages;
+        None
     }
 }
 
 type PageId = u32;
 
-pub fn write(file: &LiteDBFile) -> Vec<u8> {
-    let mut pages = PageCollection::new_collection();
+/// The result of [`write`].
+///
+/// The positions of newly written items must be recorded with [`commit`](Written::commit)
+/// if the pages are used as the base of next write.
+pub(super) struct Written {
+    pub pages: Vec<u8>,
+    data_positions: HashMap<ArenaKey<DbDocument>, PageAddress>,
+    index_positions: HashMap<ArenaKey<IndexNode>, PageAddress>,
+    collection_pages: Vec<(CaseInsensitiveString, PageId)>,
+}
+
+impl Written {
+    /// Records the positions of newly written documents, index nodes and collections to the `file`,
+    /// and returns the written pages.
+    pub fn commit(self, file: &mut LiteDBFile) -> Vec<u8> {
+        for (key, position) in self.data_positions {
+            file.data[key].position = Some(position);
+        }
+        for (key, position) in self.index_positions {
+            file.index_arena[key].position = Some(position);
+        }
+        for (name, page_id) in self.collection_pages {
+            file.collections[&name].page_id = Some(page_id);
+        }
+        self.pages
+    }
+}
+
+/// Writes the database file.
+///
+/// This patches pages as they were loaded (or last written) instead of building the file from scratch.
+/// Blocks of deleted or modified documents and index nodes are freed,
+/// and new blocks are allocated from free space in existing pages first.
+/// Therefore, only pages with changes will differ from loaded pages.
+pub(super) fn write(file: &LiteDBFile) -> Written {
+    let mut pages = PageCollection::from_pages(file.loaded_pages.to_vec());
+    let mut written = Written {
+        pages: vec![],
+        data_positions: HashMap::new(),
+        index_positions: HashMap::new(),
+        collection_pages: vec![],
+    };
+
+    if pages.len() == 0 {
+        let header = pages.new(PageType::Header);
+        assert!(header == 0, "header page must be 0");
+    }
+
+    let mut free_lists = FreeLists::load(&pages);
 
-    let header = pages.new(PageType::Header);
-    assert!(header == 0, "header page must be 0");
+    // first, free blocks no longer used
+    free_unused_blocks(&mut pages, file, &mut free_lists);
 
     let mut collections = bson::Document::new();
 
     for (name, collection) in &file.collections {
-        let page_id = write_collection(&mut pages, &file.index_arena, &file.data, collection);
+        let page_id = match collection.page_id {
+            Some(page_id) => page_id,
+            None => {
+                let page_id = pages.new(PageType::Collection);
+                pages[page_id].set_col_id(page_id);
+                written.collection_pages.push((name.clone(), page_id));
+                page_id
+            }
+        };
+        write_collection(
+            &mut pages,
+            file,
+            collection,
+            page_id,
+            &mut free_lists,
+            &mut written,
+        );
         collections.insert(&name.0, page_id as i32);
     }
 
-    // TODO: write header page
-    write_header(&mut pages, header, file, &collections);
+    write_header(&mut pages, file, &collections);
 
-    pages.data
+    written.pages = pages.data;
+    written
 }
 
 // region utility for index and data pages
@@ -68,25 +156,25 @@ fn add_free_list(pages: &mut PageCollection, page_id: PageId, start_page_id: &mu
 }
 
 fn remove_free_list(pages: &mut PageCollection, page_id: PageId, start_page_id: &mut PageId) {
+    let prev_page_id = pages[page_id].prev_page_id();
+    let next_page_id = pages[page_id].next_page_id();
+
     // fix prev page
-    if pages[page_id].prev_page_id() != u32::MAX {
-        let prev_page_id = pages[page_id].prev_page_id();
-        pages[prev_page_id].set_next_page_id(page_id);
+    if prev_page_id != u32::MAX {
+        pages[prev_page_id].set_next_page_id(next_page_id);
     }
 
     // fix next page
-    if pages[page_id].next_page_id() != u32::MAX {
-        let next_page_id = pages[page_id].next_page_id();
-        pages[next_page_id].set_next_page_id(page_id);
+    if next_page_id != u32::MAX {
+        pages[next_page_id].set_prev_page_id(prev_page_id);
     }
 
     // if page is first of the list set firstPage as next page
     if *start_page_id == page_id {
-        *start_page_id = pages[page_id].next_page_id();
+        *start_page_id = next_page_id;
 
         debug_assert!(
-            pages[page_id].next_page_id() == u32::MAX
-                || pages[pages[page_id].next_page_id()].page_type() != Some(PageType::Empty),
+            next_page_id == u32::MAX || pages[next_page_id].page_type() != Some(PageType::Empty),
             "first page on free stack must be non empty page"
         );
     }
@@ -96,35 +184,193 @@ fn remove_free_list(pages: &mut PageCollection, page_id: PageId, start_page_id:
     pages[page_id].set_next_page_id(u32::MAX);
 }
 
+/// The free page lists of all collections in the file
+struct FreeLists {
+    /// Data page lists for each collection page
+    data: HashMap<PageId, DataPageManager>,
+    /// Index page lists for each collection page and index slot
+    index: HashMap<(PageId, u8), IndexPageManager>,
+}
+
+impl FreeLists {
+    /// Reads free lists from all collection pages, including ones no longer used
+    fn load(pages: &PageCollection) -> Self {
+        let mut data = HashMap::new();
+        let mut index = HashMap::new();
+
+        for page_id in 0..pages.len() {
+            if pages[page_id].page_type() != Some(PageType::Collection) {
+                continue;
+            }
+            let Ok(collection) = RawCollectionPage::parse(&pages[page_id]) else {
+                continue;
+            };
+
+            let mut data_pages = DataPageManager::new(page_id);
+            data_pages.free_pages = collection.free_data_page_list;
+            data.insert(page_id, data_pages);
+
+            for raw_index in collection.indexes.values() {
+                let mut index_pages = IndexPageManager::new(page_id);
+                index_pages.free_page = raw_index.free_index_page_list;
+                index.insert((page_id, raw_index.slot), index_pages);
+            }
+        }
+
+        Self { data, index }
+    }
+
+    fn data(&mut self, col_id: PageId) -> &mut DataPageManager {
+        self.data
+            .entry(col_id)
+            .or_insert_with(|| DataPageManager::new(col_id))
+    }
+
+    fn index(&mut self, col_id: PageId, slot: u8) -> &mut IndexPageManager {
+        self.index
+            .entry((col_id, slot))
+            .or_insert_with(|| IndexPageManager::new(col_id))
+    }
+}
+
+/// Frees data blocks and index nodes not used by current file, and deletes collection pages no longer used.
+fn free_unused_blocks(pages: &mut PageCollection, file: &LiteDBFile, free_lists: &mut FreeLists) {
+    let mut used_data_blocks = HashSet::new();
+    let mut used_index_nodes = HashSet::new();
+    let mut used_collection_pages = HashSet::new();
+
+    for collection in file.collections.values() {
+        if let Some(page_id) = collection.page_id {
+            used_collection_pages.insert(page_id);
+        }
+
+        for index in collection.indexes.values() {
+            let nodes = IndexHelper::find_all(&file.index_arena, index, InternalOrder::Ascending);
+            for index_key in [index.head, index.tail].into_iter().chain(nodes) {
+                if let Some(position) = file.index_arena[index_key].position {
+                    used_index_nodes.insert(position);
+                }
+            }
+        }
+
+        let pk_nodes = IndexHelper::find_all(
+            &file.index_arena,
+            collection.pk_index(),
+            InternalOrder::Ascending,
+        );
+        for index_key in pk_nodes {
+            let data = &file.data[file.index_arena[index_key].data.unwrap()];
+            let mut current = data.position.unwrap_or(PageAddress::EMPTY);
+            while !current.is_empty() {
+                used_data_blocks.insert(current);
+                current = pages[current.page_id()]
+                    .get_block(current.index())
+                    .read_page_address(offsets::data_block::P_NEXT_BLOCK);
+            }
+        }
+    }
+
+    let mut unused_collection_pages = vec![];
+
+    for page_id in 1..pages.len() {
+        let used_blocks = match pages[page_id].page_type() {
+            Some(PageType::Data) => &used_data_blocks,
+            Some(PageType::Index) => &used_index_nodes,
+            Some(PageType::Collection) => {
+                if !used_collection_pages.contains(&page_id) {
+                    unused_collection_pages.push(page_id);
+                }
+                continue;
+            }
+            _ => continue,
+        };
+
+        let unused = pages[page_id]
+            .blocks()
+            .map(|(index, _)| index)
+            .filter(|&index| !used_blocks.contains(&PageAddress::new(page_id, index)))
+            .collect::<Vec<_>>();
+
+        if unused.is_empty() {
+            continue;
+        }
+
+        let col_id = pages[page_id].col_id();
+
+        if pages[page_id].page_type() == Some(PageType::Data) {
+            for index in unused {
+                pages[page_id].delete_block(index);
+            }
+            free_lists
+                .data(col_id)
+                .add_or_remove_free_data_list(pages, page_id);
+        } else {
+            // index page is used by single index
+            let slot = pages[page_id]
+                .get_block(unused[0])
+                .read_u8(offsets::index_node::P_SLOT);
+            for index in unused {
+                pages[page_id].delete_block(index);
+            }
+            free_lists
+                .index(col_id, slot)
+                .add_or_remove_free_index_list(pages, page_id);
+        }
+    }
+
+    for page_id in unused_collection_pages {
+        pages.delete_page(page_id);
+    }
+}
+
 // endregion
 
 fn write_collection(
     pages: &mut PageCollection,
-    indexes: &KeyArena<IndexNode>,
-    data_arena: &KeyArena<DbDocument>,
+    file: &LiteDBFile,
     collection: &Collection,
-) -> PageId {
-    let collection_page = pages.new(PageType::Collection);
-    pages[collection_page].set_col_id(collection_page);
-
+    collection_page: PageId,
+    free_lists: &mut FreeLists,
+    written: &mut Written,
+) {
     // first, write data to data pages
-    let (data_blocks, free_data_pages) =
-        write_collection_data(pages, indexes, data_arena, collection_page, collection);
+    write_collection_data(
+        pages,
+        file,
+        collection_page,
+        collection,
+        free_lists.data(collection_page),
+        &mut written.data_positions,
+    );
 
-    // then insert index nodes
-    let (index_nodes, free_index_pages) = write_indexes(
+    // then insert and link index nodes
+    write_indexes(
         pages,
-        indexes,
-        data_arena,
+        file,
         collection,
         collection_page,
-        &data_blocks,
+        free_lists,
+        written,
     );
 
     // finally serialize collection page
+    let free_data_pages = free_lists.data(collection_page).free_pages;
+    let free_index_pages = collection
+        .indexes
+        .values()
+        .map(|index| free_lists.index(collection_page, index.slot).free_page)
+        .collect::<Vec<_>>();
+
+    let address_of = |index_key: ArenaKey<IndexNode>| {
+        file.index_arena[index_key]
+            .position
+            .unwrap_or_else(|| written.index_positions[&index_key])
+    };
+
     let page = &mut pages[collection_page];
 
     let area = page.slice_mut(PAGE_HEADER_SIZE, PAGE_SIZE - PAGE_HEADER_SIZE);
+    area.as_bytes_mut().fill(0);
     let mut writer = BufferWriter::single(area);
 
     for x in free_data_pages {
@@ -135,19 +381,17 @@ fn write_collection(
 
     writer.write_u8(collection.indexes.len() as u8);
 
-    for index in collection.indexes.values() {
+    for (index, free_index_page) in collection.indexes.values().zip(free_index_pages) {
         writer.write_u8(index.slot);
         writer.write_u8(index.index_type);
         writer.write_cstring(&index.name);
         writer.write_cstring(&index.expression);
         writer.write_bool(index.unique);
-        writer.write_page_address(index_nodes[&index.head]);
-        writer.write_page_address(index_nodes[&index.tail]);
+        writer.write_page_address(address_of(index.head));
+        writer.write_page_address(address_of(index.tail));
         writer.write_u8(index.reserved);
-        writer.write_u32(free_index_pages[index.name.as_str()]);
+        writer.write_u32(free_index_page);
     }
-
-    collection_page
 }
 
 // region data page utilities
@@ -162,7 +406,7 @@ pub fn data_free_index_slot(free_bytes: usize) -> u8 {
     FREE_PAGE_SLOTS
         .iter()
         .enumerate()
-        .find(|&(_, &slot)| slot >= free_bytes)
+        .find(|&(_, &slot)| free_bytes >= slot)
         .map(|(index, _)| index as u8)
         .unwrap_or((PAGE_FREE_LIST_SLOTS - 1) as u8)
 }
@@ -230,13 +474,12 @@ impl DataPageManager {
 
         // remove from intial slot
         if initial_slot != u8::MAX {
-            remove_free_list(pages, page_id, &mut self.free_pages[new_slot as usize]);
+            remove_free_list(pages, page_id, &mut self.free_pages[initial_slot as usize]);
         }
 
         // if there is no items, delete page
         if items_count == 0 {
-            unreachable!("No items will removed");
-            //this.DeletePage(page);
+            pages.delete_page(page_id);
         } else {
             // add into current slot
             add_free_list(pages, page_id, &mut self.free_pages[new_slot as usize]);
@@ -248,25 +491,30 @@ impl DataPageManager {
 
 // endregion
 
+/// Writes documents not written yet (or modified) to data pages
 fn write_collection_data(
     pages: &mut PageCollection,
-    indexes: &KeyArena<IndexNode>,
-    data_arena: &KeyArena<DbDocument>,
+    file: &LiteDBFile,
     collection_page: PageId,
     collection: &Collection,
-) -> (
-    HashMap<ArenaKey<DbDocument>, PageAddress>,
-    [u32; PAGE_FREE_LIST_SLOTS],
+    data_pages: &mut DataPageManager,
+    data_positions: &mut HashMap<ArenaKey<DbDocument>, PageAddress>,
 ) {
-    let mut data_slots = HashMap::new();
+    debug_assert_eq!(data_pages.col_id, collection_page);
 
-    let mut data_pages = DataPageManager::new(collection_page);
-    for index_key in IndexHelper::find_all(indexes, collection.pk_index(), InternalOrder::Ascending)
-    {
-        let data_key = indexes[index_key].data.unwrap();
-        let data = &data_arena[data_key].data;
+    for index_key in IndexHelper::find_all(
+        &file.index_arena,
+        collection.pk_index(),
+        InternalOrder::Ascending,
+    ) {
+        let data_key = file.index_arena[index_key].data.unwrap();
+        if file.data[data_key].position.is_some() {
+            continue;
+        }
+        let data = &file.data[data_key].data;
         let length = data.get_serialized_value_len();
-        assert!(length <= MAX_DOCUMENT_SIZE);
+        // the size is checked on insert and update
+        debug_assert!(length <= MAX_DOCUMENT_SIZE);
 
         struct DataSegmentIterator<'a, 'b> {
             pages: &'a mut PageCollection,
@@ -324,7 +572,7 @@ fn write_collection_data(
 
         let iterator = DataSegmentIterator {
             pages,
-            data_pages: &mut data_pages,
+            data_pages,
             remaining: length,
             block_index: 0,
             last_block: &mut last_block,
@@ -333,10 +581,8 @@ fn write_collection_data(
 
         BufferWriter::fragmented(iterator).write_document(data);
 
-        data_slots.insert(data_key, first_block.unwrap());
+        data_positions.insert(data_key, first_block.unwrap());
     }
-
-    (data_slots, data_pages.free_pages)
 }
 
 // region index page utilities
@@ -401,13 +647,11 @@ impl IndexPageManager {
 
         // first, test if page should be deleted
         if pages[page_id].items_count() == 0 {
-            unreachable!("no nodes will be removed");
-            //if (is_on_list)
-            //{
-            //    this.RemoveFreeList(page, ref startPageID);
-            //}
-            //
-            //this.DeletePage(page);
+            if is_on_list {
+                remove_free_list(pages, page_id, &mut self.free_page);
+            }
+
+            pages.delete_page(page_id);
         } else {
             if is_on_list && !must_keep {
                 remove_free_list(pages, page_id, &mut self.free_page);
@@ -424,28 +668,24 @@ impl IndexPageManager {
 
 // endregion
 
-fn write_indexes<'a>(
+/// Writes index nodes not written yet, and updates links of all index nodes
+fn write_indexes(
     pages: &mut PageCollection,
-    indexes: &KeyArena<IndexNode>,
-    data_arena: &KeyArena<DbDocument>,
-    collection: &'a Collection,
+    file: &LiteDBFile,
+    collection: &Collection,
     collection_page: PageId,
-    data_blocks: &HashMap<ArenaKey<DbDocument>, PageAddress>,
-) -> (
-    HashMap<ArenaKey<IndexNode>, PageAddress>,
-    HashMap<&'a str, u32>,
+    free_lists: &mut FreeLists,
+    written: &mut Written,
 ) {
-    let mut index_nodes = HashMap::new();
-    let mut free_page = HashMap::new();
+    let indexes = &file.index_arena;
 
-    // first pass: write most information except for linking information
+    // first pass: write new nodes except for linking information
     for index in collection.indexes.values() {
-        let mut index_manager = IndexPageManager::new(collection_page);
+        let index_manager = free_lists.index(collection_page, index.slot);
 
         fn add_index_node(
             pages: &mut PageCollection,
             indexes: &KeyArena<IndexNode>,
-            data_blocks: &HashMap<ArenaKey<DbDocument>, PageAddress>,
             index_manager: &mut IndexPageManager,
             index_key: ArenaKey<IndexNode>,
         ) -> PageAddress {
@@ -460,14 +700,7 @@ fn write_indexes<'a>(
 
             block.write_u8(offsets::index_node::P_SLOT, index_node.slot);
             block.write_u8(offsets::index_node::P_LEVELS, index_node.levels);
-            block.write_page_address(
-                offsets::index_node::P_DATA_BLOCK,
-                index_node
-                    .data
-                    .map(|x| data_blocks[&x])
-                    .unwrap_or(PageAddress::EMPTY),
-            );
-            // next and prev/next are later
+            // data block, next node, and prev/next are later
             block.write_index_key(
                 offsets::index_node::calc_key_ptr(index_node.levels),
                 &index_node.key,
@@ -476,73 +709,52 @@ fn write_indexes<'a>(
             PageAddress::new(index_page, node_idx)
         }
 
-        // first add head / tail node
-        index_nodes.insert(
-            index.head,
-            add_index_node(pages, indexes, data_blocks, &mut index_manager, index.head),
-        );
-        index_nodes.insert(
-            index.tail,
-            add_index_node(pages, indexes, data_blocks, &mut index_manager, index.tail),
-        );
-
-        // and then actual data nodes
-        for index_key in IndexHelper::find_all(indexes, index, InternalOrder::Ascending) {
-            index_nodes.insert(
-                index_key,
-                add_index_node(pages, indexes, data_blocks, &mut index_manager, index_key),
-            );
+        let nodes = IndexHelper::find_all(indexes, index, InternalOrder::Ascending);
+        for index_key in [index.head, index.tail].into_iter().chain(nodes) {
+            if indexes[index_key].position.is_none() {
+                let position = add_index_node(pages, indexes, index_manager, index_key);
+                written.index_positions.insert(index_key, position);
+            }
         }
-
-        free_page.insert(index.name.as_str(), index_manager.free_page);
     }
 
-    fn link(
-        block: &mut BufferSlice,
-        offset: usize,
-        node: Option<ArenaKey<IndexNode>>,
-        index_nodes: &HashMap<ArenaKey<IndexNode>, PageAddress>,
-    ) {
-        block.write_page_address(
-            offset,
-            node.map(|x| index_nodes[&x]).unwrap_or(PageAddress::EMPTY),
-        )
-    }
+    let index_nodes = |index_key: Option<ArenaKey<IndexNode>>| {
+        index_key.map_or(PageAddress::EMPTY, |index_key| {
+            indexes[index_key]
+                .position
+                .unwrap_or_else(|| written.index_positions[&index_key])
+        })
+    };
+    let data_blocks = |data_key: Option<ArenaKey<DbDocument>>| {
+        data_key.map_or(PageAddress::EMPTY, |data_key| {
+            file.data[data_key]
+                .position
+                .unwrap_or_else(|| written.data_positions[&data_key])
+        })
+    };
 
     // second pass: link nodes
     for index in collection.indexes.values() {
-        fn link_node(
-            pages: &mut PageCollection,
-            indexes: &KeyArena<IndexNode>,
-            index_nodes: &HashMap<ArenaKey<IndexNode>, PageAddress>,
-            index_key: ArenaKey<IndexNode>,
-        ) {
-            let address = index_nodes[&index_key];
+        let nodes = IndexHelper::find_all(indexes, index, InternalOrder::Ascending);
+        for index_key in [index.head, index.tail].into_iter().chain(nodes) {
+            let address = index_nodes(Some(index_key));
             let block = pages[address.page_id()].get_block_mut(address.index());
             let node = &indexes[index_key];
 
+            block.write_page_address(offsets::index_node::P_DATA_BLOCK, data_blocks(node.data));
+
             for i in 0..node.levels as usize {
-                link(
-                    block,
-                    offsets::index_node::P_PREV_NEXT + i * PageAddress::SERIALIZED_SIZE * 2,
-                    node.prev[i],
-                    index_nodes,
-                );
-                link(
-                    block,
-                    offsets::index_node::P_PREV_NEXT
-                        + i * PageAddress::SERIALIZED_SIZE * 2
-                        + PageAddress::SERIALIZED_SIZE,
-                    node.next[i],
-                    index_nodes,
+                let offset =
+                    offsets::index_node::P_PREV_NEXT + i * PageAddress::SERIALIZED_SIZE * 2;
+                block.write_page_address(offset, index_nodes(node.prev[i]));
+                block.write_page_address(
+                    offset + PageAddress::SERIALIZED_SIZE,
+                    index_nodes(node.next[i]),
                 );
             }
-        }
 
-        link_node(pages, indexes, &index_nodes, index.head);
-        link_node(pages, indexes, &index_nodes, index.tail);
-        for index_key in IndexHelper::find_all(indexes, index, InternalOrder::Ascending) {
-            link_node(pages, indexes, &index_nodes, index_key);
+            // head and tail node does not have next node
+            block.write_page_address(offsets::index_node::P_NEXT_NODE, PageAddress::EMPTY);
         }
     }
 
@@ -550,43 +762,33 @@ fn write_indexes<'a>(
     {
         let index = collection.pk_index();
         for index_key in IndexHelper::find_all(indexes, index, InternalOrder::Ascending) {
-            let data = &data_arena[indexes[index_key].data.unwrap()];
+            let data = &file.data[indexes[index_key].data.unwrap()];
             for (&lead, &trail) in data.index_nodes.iter().tuple_windows::<(_, _)>() {
-                let lead = index_nodes[&lead];
-                let trail = index_nodes[&trail];
+                let lead = index_nodes(Some(lead));
+                let trail = index_nodes(Some(trail));
 
                 let block = pages[lead.page_id()].get_block_mut(lead.index());
                 block.write_page_address(offsets::index_node::P_NEXT_NODE, trail);
             }
-            {
-                let lead = index_nodes[data.index_nodes.last().unwrap()];
-                let block = pages[lead.page_id()].get_block_mut(lead.index());
-                block.write_page_address(offsets::index_node::P_NEXT_NODE, PageAddress::EMPTY);
-            }
         }
     }
-
-    (index_nodes, free_page)
 }
 
-fn write_header(
-    pages: &mut PageCollection,
-    header: PageId,
-    file: &LiteDBFile,
-    collections: &bson::Document,
-) {
+fn write_header(pages: &mut PageCollection, file: &LiteDBFile, collections: &bson::Document) {
     use offsets::header_page::*;
     let last_page_id = pages.len() - 1;
+    let free_empty_page_id = pages.free_empty_page;
 
-    let header_page = &mut pages[header];
+    let header_page = &mut pages[0];
     header_page.write_bytes(P_HEADER_INFO, HEADER_INFO);
     header_page.write_byte(P_FILE_VERSION, FILE_VERSION);
-    header_page.write_u32(P_FREE_EMPTY_PAGE_ID, u32::MAX);
+    header_page.write_u32(P_FREE_EMPTY_PAGE_ID, free_empty_page_id);
     header_page.write_u32(P_LAST_PAGE_ID, last_page_id);
     header_page.write_u64(P_CREATION_TIME, file.creation_time.ticks());
     file.pragmas.update_buffer(header_page);
     header_page.write_u8(P_INVALID_DATAFILE_STATE, 0);
     let collections_area = header_page.slice_mut(P_COLLECTIONS, COLLECTIONS_SIZE);
+    collections_area.as_bytes_mut().fill(0);
     BufferWriter::single(collections_area).write_document(collections);
 }
 
@@ -594,22 +796,52 @@ fn write_header(
 /// You can access page with Index impl
 struct PageCollection {
     data: Vec<u8>,
+    free_empty_page: PageId,
 }
 
 impl PageCollection {
-    pub fn new_collection() -> PageCollection {
-        PageCollection { data: vec![] }
+    /// Creates the collection with existing pages. The header page must be at page 0 if not empty.
+    pub fn from_pages(data: Vec<u8>) -> PageCollection {
+        let mut pages = PageCollection {
+            data,
+            free_empty_page: u32::MAX,
+        };
+        if pages.len() != 0 {
+            pages.free_empty_page = pages[0].read_u32(offsets::header_page::P_FREE_EMPTY_PAGE_ID);
+        }
+        pages
     }
 
-    /// Returns page id for newly allocated page
+    /// Returns page id for newly allocated page.
+    ///
+    /// Like LiteDB, pages in the free empty page list are reused first.
     #[allow(clippy::new_ret_no_self, clippy::wrong_self_convention)]
     pub fn new(&mut self, page_type: PageType) -> PageId {
-        let new_page = self.len();
-        self.data.extend_from_slice(&[0; PAGE_SIZE]);
+        let new_page = if self.free_empty_page != u32::MAX {
+            let page_id = self.free_empty_page;
+            debug_assert_eq!(self[page_id].page_type(), Some(PageType::Empty));
+            self.free_empty_page = self[page_id].next_page_id();
+            self[page_id].as_bytes_mut().fill(0);
+            page_id
+        } else {
+            let page_id = self.len();
+            self.data.extend_from_slice(&[0; PAGE_SIZE]);
+            page_id
+        };
         self[new_page].initialize_page(new_page, page_type);
         new_page
     }
 
+    /// Marks the page as empty and adds it to the free empty page list
+    pub fn delete_page(&mut self, page_id: PageId) {
+        let free_empty_page = self.free_empty_page;
+        let page = &mut self[page_id];
+        page.as_bytes_mut().fill(0);
+        page.initialize_page(page_id, PageType::Empty);
+        page.set_next_page_id(free_empty_page);
+        self.free_empty_page = page_id;
+    }
+
     pub fn len(&self) -> u32 {
         (self.data.len() / PAGE_SIZE) as u32
     }
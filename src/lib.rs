@@ -1,13 +1,15 @@
 //! # LiteDB in Rust
 //! This is a reimplementation of [LiteDB] in Rust.
 //!
-//! This implementation (currently?) only supports single-threaded operation.
+//! [`LiteDBFile`](file_io::LiteDBFile) is a single-threaded in-memory database.
+//! To share the database between threads, use [`SharedLiteDB`](file_io::SharedLiteDB).
 //!
 //! [LiteDB]: <https://www.litedb.org/>
 
 #![allow(clippy::too_many_arguments)]
 
 use crate::bson::Value;
+use crate::utils::PageAddress;
 use std::fmt::Display;
 
 #[macro_use]
@@ -19,7 +21,7 @@ mod buffer_reader;
 mod buffer_writer;
 mod constants;
 pub mod file_io;
-#[cfg(all(feature = "shared-mutex", windows))]
+#[cfg(all(feature = "shared-mutex", any(windows, target_os = "linux")))]
 pub mod shared_mutex;
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -29,6 +31,7 @@ pub struct Error(Box<ErrorImpl>);
 
 use err_impl::Error as ErrorImpl;
 use err_impl::ParseError as ParseErrorImpl;
+use err_impl::ParseErrorContext;
 
 mod err_impl {
     use super::*;
@@ -37,10 +40,17 @@ mod err_impl {
         Eval(String),
 
         InvalidIndexKeyType,
-        IndexKeySizeExceeded,
+        IndexKeySizeExceeded { index: String, id: Value },
         DuplicatedIndexKey { index: String, key: Value },
         IndexAlreadyExists(String),
         InvalidFieldType { field: String, value: Value },
+        InvalidPragmaValue(String),
+        InvalidIndex { name: String, reason: &'static str },
+        DropIdIndex,
+        InvalidCollectionName { name: String, reason: &'static str },
+        InvalidDocumentKey(String),
+        DocumentSizeExceeded { id: Value, size: usize },
+        EncryptedLog,
     }
 
     #[derive(Debug)]
@@ -50,8 +60,54 @@ mod err_impl {
         InvalidBson,
         BadReference,
         NoIdIndex,
+        Encrypted,
+        InvalidPassword,
         Expression(expression::ParseError),
     }
+
+    /// Where the [`ParseError`](super::ParseError) is found.
+    ///
+    /// The innermost context is kept when the error is propagated.
+    #[derive(Debug, Default)]
+    pub(crate) struct ParseErrorContext {
+        pub page_id: Option<u32>,
+        pub collection: Option<String>,
+        pub index: Option<String>,
+        pub address: Option<PageAddress>,
+        pub offset: Option<usize>,
+    }
+}
+
+/// The kind of [`Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Failed to evaluate an expression.
+    Eval,
+    /// The index key is MinValue, MaxValue, or a document.
+    InvalidIndexKeyType,
+    /// The index key is longer than 1023 bytes.
+    IndexKeySizeExceeded,
+    /// The key already exists in the unique index.
+    DuplicatedIndexKey,
+    /// The index with the same name but different expression already exists.
+    IndexAlreadyExists,
+    /// The field has the value of invalid type, like null `_id`.
+    InvalidFieldType,
+    /// The pragma value is out of range.
+    InvalidPragmaValue,
+    /// The index name or expression is not valid.
+    InvalidIndex,
+    /// Dropping the primary key index.
+    DropIdIndex,
+    /// The collection name is not valid.
+    InvalidCollectionName,
+    /// The document key contains null char.
+    InvalidDocumentKey,
+    /// The serialized document is larger than the maximum document size.
+    DocumentSizeExceeded,
+    /// Writing the log file of an encrypted database, which is not supported.
+    EncryptedLog,
 }
 
 impl Error {
@@ -59,12 +115,93 @@ impl Error {
         Error(Box::new(inner))
     }
 
+    pub fn kind(&self) -> ErrorKind {
+        match self.0.as_ref() {
+            ErrorImpl::Eval(_) => ErrorKind::Eval,
+            ErrorImpl::InvalidIndexKeyType => ErrorKind::InvalidIndexKeyType,
+            ErrorImpl::IndexKeySizeExceeded { .. } => ErrorKind::IndexKeySizeExceeded,
+            ErrorImpl::DuplicatedIndexKey { .. } => ErrorKind::DuplicatedIndexKey,
+            ErrorImpl::IndexAlreadyExists(_) => ErrorKind::IndexAlreadyExists,
+            ErrorImpl::InvalidFieldType { .. } => ErrorKind::InvalidFieldType,
+            ErrorImpl::InvalidPragmaValue(_) => ErrorKind::InvalidPragmaValue,
+            ErrorImpl::InvalidIndex { .. } => ErrorKind::InvalidIndex,
+            ErrorImpl::DropIdIndex => ErrorKind::DropIdIndex,
+            ErrorImpl::InvalidCollectionName { .. } => ErrorKind::InvalidCollectionName,
+            ErrorImpl::InvalidDocumentKey(_) => ErrorKind::InvalidDocumentKey,
+            ErrorImpl::DocumentSizeExceeded { .. } => ErrorKind::DocumentSizeExceeded,
+            ErrorImpl::EncryptedLog => ErrorKind::EncryptedLog,
+        }
+    }
+
+    /// Returns the name of the index the error is about.
+    pub fn index_name(&self) -> Option<&str> {
+        match self.0.as_ref() {
+            ErrorImpl::IndexKeySizeExceeded { index, .. } => Some(index),
+            ErrorImpl::DuplicatedIndexKey { index, .. } => Some(index),
+            ErrorImpl::IndexAlreadyExists(name) => Some(name),
+            ErrorImpl::InvalidIndex { name, .. } => Some(name),
+            ErrorImpl::DropIdIndex => Some("_id"),
+            _ => None,
+        }
+    }
+
+    /// Returns the duplicated key for [`ErrorKind::DuplicatedIndexKey`],
+    /// or the invalid value for [`ErrorKind::InvalidFieldType`].
+    pub fn key(&self) -> Option<&Value> {
+        match self.0.as_ref() {
+            ErrorImpl::DuplicatedIndexKey { key, .. } => Some(key),
+            ErrorImpl::InvalidFieldType { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the field for [`ErrorKind::InvalidFieldType`],
+    /// or the key for [`ErrorKind::InvalidDocumentKey`].
+    pub fn field(&self) -> Option<&str> {
+        match self.0.as_ref() {
+            ErrorImpl::InvalidFieldType { field, .. } => Some(field),
+            ErrorImpl::InvalidDocumentKey(key) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the collection for [`ErrorKind::InvalidCollectionName`].
+    pub fn collection_name(&self) -> Option<&str> {
+        match self.0.as_ref() {
+            ErrorImpl::InvalidCollectionName { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the `_id` of the document exceeding the size limit,
+    /// for [`ErrorKind::DocumentSizeExceeded`] and [`ErrorKind::IndexKeySizeExceeded`].
+    pub fn document_id(&self) -> Option<&Value> {
+        match self.0.as_ref() {
+            ErrorImpl::IndexKeySizeExceeded { id, .. } => Some(id),
+            ErrorImpl::DocumentSizeExceeded { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Returns the limit in bytes exceeded,
+    /// for [`ErrorKind::DocumentSizeExceeded`] and [`ErrorKind::IndexKeySizeExceeded`].
+    pub fn size_limit(&self) -> Option<usize> {
+        match self.0.as_ref() {
+            ErrorImpl::IndexKeySizeExceeded { .. } => Some(constants::MAX_INDEX_KEY_LENGTH),
+            ErrorImpl::DocumentSizeExceeded { .. } => Some(constants::MAX_DOCUMENT_SIZE),
+            _ => None,
+        }
+    }
+
     pub(crate) fn invalid_index_key_type() -> Error {
         Error::new(ErrorImpl::InvalidIndexKeyType)
     }
 
-    pub(crate) fn index_key_too_long() -> Error {
-        Error::new(ErrorImpl::IndexKeySizeExceeded)
+    pub(crate) fn index_key_too_long(index: &str, id: &Value) -> Error {
+        Error::new(ErrorImpl::IndexKeySizeExceeded {
+            index: index.to_string(),
+            id: id.clone(),
+        })
     }
 
     pub(crate) fn index_duplicate_key(index: &str, key: Value) -> Error {
@@ -85,6 +222,44 @@ impl Error {
         })
     }
 
+    pub(crate) fn invalid_pragma_value(message: String) -> Error {
+        Error::new(ErrorImpl::InvalidPragmaValue(message))
+    }
+
+    pub(crate) fn invalid_index(name: &str, reason: &'static str) -> Error {
+        Error::new(ErrorImpl::InvalidIndex {
+            name: name.to_string(),
+            reason,
+        })
+    }
+
+    pub(crate) fn drop_id_index() -> Error {
+        Error::new(ErrorImpl::DropIdIndex)
+    }
+
+    pub(crate) fn invalid_collection_name(name: &str, reason: &'static str) -> Error {
+        Error::new(ErrorImpl::InvalidCollectionName {
+            name: name.to_string(),
+            reason,
+        })
+    }
+
+    pub(crate) fn invalid_document_key(key: String) -> Error {
+        Error::new(ErrorImpl::InvalidDocumentKey(key))
+    }
+
+    pub(crate) fn document_too_large(id: &Value, size: usize) -> Error {
+        Error::new(ErrorImpl::DocumentSizeExceeded {
+            id: id.clone(),
+            size,
+        })
+    }
+
+    #[cfg_attr(not(feature = "encryption"), allow(dead_code))]
+    pub(crate) fn encrypted_log() -> Error {
+        Error::new(ErrorImpl::EncryptedLog)
+    }
+
     pub(crate) fn expr_run_error(str: &str) -> Self {
         Self::new(ErrorImpl::Eval(format!("executing: {}", str)))
     }
@@ -98,7 +273,11 @@ impl Display for Error {
             ErrorImpl::InvalidIndexKeyType => f.write_str(
                 "Invalid index key: Min/Max or Document Value are not supported as index key",
             ),
-            ErrorImpl::IndexKeySizeExceeded => f.write_str("Invalid index key: Index key too long"),
+            ErrorImpl::IndexKeySizeExceeded { index, id } => write!(
+                f,
+                "Invalid index key: Index key too long in index `{index}` for document {id:?}, limit is {} bytes",
+                constants::MAX_INDEX_KEY_LENGTH
+            ),
             ErrorImpl::DuplicatedIndexKey { index, key } => write!(
                 f,
                 "Duplicate index key in unique index `{index}`, key: {key:?}"
@@ -107,6 +286,25 @@ impl Display for Error {
             ErrorImpl::InvalidFieldType { field, value } => {
                 write!(f, "Invalid field type: {field}, value: {value:?}")
             }
+            ErrorImpl::InvalidPragmaValue(message) => f.write_str(message),
+            ErrorImpl::InvalidIndex { name, reason } => {
+                write!(f, "Invalid index '{name}': {reason}")
+            }
+            ErrorImpl::DropIdIndex => f.write_str("Primary key index '_id' can't be dropped"),
+            ErrorImpl::InvalidCollectionName { name, reason } => {
+                write!(f, "Invalid collection name '{name}': {reason}")
+            }
+            ErrorImpl::InvalidDocumentKey(key) => {
+                write!(f, "Invalid document key {key:?}: null char is not allowed")
+            }
+            ErrorImpl::DocumentSizeExceeded { id, size } => write!(
+                f,
+                "Document {id:?} is too large: {size} bytes, limit is {} bytes",
+                constants::MAX_DOCUMENT_SIZE
+            ),
+            ErrorImpl::EncryptedLog => {
+                f.write_str("Writing log file of encrypted database is not supported")
+            }
         }
     }
 }
@@ -119,9 +317,115 @@ impl std::fmt::Debug for Error {
 
 impl std::error::Error for Error {}
 
-pub struct ParseError(Box<ParseErrorImpl>);
+pub struct ParseError(Box<(ParseErrorImpl, ParseErrorContext)>);
+
+/// The kind of [`ParseError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ParseErrorKind {
+    /// The file is not a LiteDB v5 database.
+    InvalidDatabase,
+    /// The page id does not match the position in the file.
+    InvalidPage,
+    /// The BSON document is malformed.
+    InvalidBson,
+    /// The page address points to a missing page or block.
+    BadReference,
+    /// The collection does not have `_id` index.
+    NoIdIndex,
+    /// The database is encrypted, but no password is provided.
+    Encrypted,
+    /// The password is wrong.
+    InvalidPassword,
+    /// The index expression cannot be parsed.
+    /// See [`ParseError::expression_error`] for details.
+    Expression,
+}
 
 impl ParseError {
+    pub fn kind(&self) -> ParseErrorKind {
+        match &self.0.0 {
+            ParseErrorImpl::InvalidDatabase => ParseErrorKind::InvalidDatabase,
+            ParseErrorImpl::InvalidPage(_) => ParseErrorKind::InvalidPage,
+            ParseErrorImpl::InvalidBson => ParseErrorKind::InvalidBson,
+            ParseErrorImpl::BadReference => ParseErrorKind::BadReference,
+            ParseErrorImpl::NoIdIndex => ParseErrorKind::NoIdIndex,
+            ParseErrorImpl::Encrypted => ParseErrorKind::Encrypted,
+            ParseErrorImpl::InvalidPassword => ParseErrorKind::InvalidPassword,
+            ParseErrorImpl::Expression(_) => ParseErrorKind::Expression,
+        }
+    }
+
+    /// Returns the id of the page the error is found in.
+    ///
+    /// For [`ParseErrorKind::InvalidPage`], this is the invalid page.
+    pub fn page_id(&self) -> Option<u32> {
+        match &self.0.0 {
+            ParseErrorImpl::InvalidPage(id) => Some(*id),
+            _ => self.0.1.page_id,
+        }
+    }
+
+    /// Returns the name of the collection being parsed when the error is found.
+    pub fn collection(&self) -> Option<&str> {
+        self.0.1.collection.as_deref()
+    }
+
+    /// Returns the name of the index being rebuilt when the error is found.
+    pub fn index(&self) -> Option<&str> {
+        self.0.1.index.as_deref()
+    }
+
+    /// Returns the address of the block the error is about as `(page_id, block_index)`.
+    ///
+    /// For [`ParseErrorKind::BadReference`], this is the dangling reference.
+    /// For [`ParseErrorKind::InvalidBson`], this is the (first) block of the malformed value.
+    pub fn address(&self) -> Option<(u32, u8)> {
+        self.0.1.address.map(|x| (x.page_id(), x.index()))
+    }
+
+    /// Returns the byte offset where the malformed BSON is detected.
+    ///
+    /// For documents, the offset is counted from the start of the document in the content of
+    /// the data blocks. For index keys, this is the offset of the key in the index node block.
+    pub fn offset(&self) -> Option<usize> {
+        self.0.1.offset
+    }
+
+    /// Returns the error of the index expression for [`ParseErrorKind::Expression`].
+    pub fn expression_error(&self) -> Option<&expression::ParseError> {
+        match &self.0.0 {
+            ParseErrorImpl::Expression(inner) => Some(inner),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn with_page(mut self, page_id: u32) -> Self {
+        self.0.1.page_id.get_or_insert(page_id);
+        self
+    }
+
+    pub(crate) fn with_collection(mut self, name: &str) -> Self {
+        self.0.1.collection.get_or_insert_with(|| name.to_string());
+        self
+    }
+
+    pub(crate) fn with_index(mut self, name: &str) -> Self {
+        self.0.1.index.get_or_insert_with(|| name.to_string());
+        self
+    }
+
+    /// Sets the address, and the page of the address if the page is not set
+    pub(crate) fn with_address(mut self, address: PageAddress) -> Self {
+        self.0.1.address.get_or_insert(address);
+        self.with_page(address.page_id())
+    }
+
+    pub(crate) fn with_offset(mut self, offset: usize) -> Self {
+        self.0.1.offset.get_or_insert(offset);
+        self
+    }
+
     fn invalid_database() -> Self {
         Self::new(ParseErrorImpl::InvalidDatabase)
     }
@@ -142,8 +446,17 @@ impl ParseError {
         Self::new(ParseErrorImpl::InvalidBson)
     }
 
+    fn encrypted() -> Self {
+        Self::new(ParseErrorImpl::Encrypted)
+    }
+
+    #[cfg_attr(not(feature = "encryption"), allow(dead_code))]
+    fn invalid_password() -> Self {
+        Self::new(ParseErrorImpl::InvalidPassword)
+    }
+
     fn new(inner: ParseErrorImpl) -> ParseError {
-        Self(Box::new(inner))
+        Self(Box::new((inner, ParseErrorContext::default())))
     }
 }
 
@@ -169,19 +482,46 @@ impl std::error::Error for ParseError {}
 
 impl std::fmt::Debug for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        <ParseErrorImpl as std::fmt::Debug>::fmt(&self.0, f)
+        f.debug_tuple("ParseError")
+            .field(&self.0.0)
+            .field(&self.0.1)
+            .finish()
     }
 }
 
 impl Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self.0.as_ref() {
+        match &self.0.0 {
             ParseErrorImpl::InvalidDatabase => write!(f, "Invalid database"),
             ParseErrorImpl::InvalidPage(id) => write!(f, "Invalid page at {id}"),
             ParseErrorImpl::InvalidBson => write!(f, "Invalid BSON"),
             ParseErrorImpl::BadReference => write!(f, "Bad reference"),
             ParseErrorImpl::NoIdIndex => write!(f, "No _id index found for collection"),
+            ParseErrorImpl::Encrypted => write!(f, "Database is encrypted; password is required"),
+            ParseErrorImpl::InvalidPassword => write!(f, "Invalid password"),
             ParseErrorImpl::Expression(inner) => Display::fmt(inner, f),
+        }?;
+
+        let context = &self.0.1;
+        let mut details = Vec::new();
+        if let Some(page_id) = context.page_id {
+            details.push(format!("page {page_id}"));
+        }
+        if let Some(collection) = &context.collection {
+            details.push(format!("collection `{collection}`"));
+        }
+        if let Some(index) = &context.index {
+            details.push(format!("index `{index}`"));
+        }
+        if let Some(address) = context.address {
+            details.push(format!("address {}:{}", address.page_id(), address.index()));
+        }
+        if let Some(offset) = context.offset {
+            details.push(format!("offset {offset}"));
+        }
+        if !details.is_empty() {
+            write!(f, " ({})", details.join(", "))?;
         }
+        Ok(())
     }
 }
@@ -5,9 +5,15 @@
 //! On windows, this module is based on shared windows mutex ([`CreateMutexExW`])
 //! and this is machine-shared lock.
 //!
+//! On linux, this module is compatible with named mutex implementation of .NET runtime (PAL),
+//! which is process-shared robust pthread mutex placed on the shared memory file
+//! at `/tmp/.dotnet/shm/global/<name>`.
+//!
 //! [Mutex in C#]: https://learn.microsoft.com/en-us/dotnet/api/system.threading.mutex?view=net-8.0
-//! [`CreateMutexExW`]: windows::Win32::System::Threading::CreateMutexExW
+//! [`CreateMutexExW`]: https://learn.microsoft.com/en-us/windows/win32/api/synchapi/nf-synchapi-createmutexexw
 
+#[cfg(target_os = "linux")]
+use linux::*;
 #[cfg(windows)]
 use windows::*;
 
@@ -201,3 +207,345 @@ mod windows {
         }
     }
 }
+
+#[cfg(target_os = "linux")]
+mod linux {
+    //! The implementation based on `src/coreclr/pal/src/synchobj/mutex.cpp` and
+    //! `src/coreclr/pal/src/sharedmemory/sharedmemory.cpp` of dotnet/runtime.
+
+    use futures::channel::oneshot;
+    use std::ffi::OsStr;
+    use std::fs::File;
+    use std::io;
+    use std::os::fd::AsRawFd;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt, PermissionsExt};
+    use std::path::{Path, PathBuf};
+    use std::sync::{Arc, Mutex};
+
+    /// `SharedMemoryType::Mutex`
+    const SHARED_MEMORY_TYPE_MUTEX: u8 = 0;
+    /// `SharedMemoryType` version for mutex
+    const SHARED_MEMORY_MUTEX_VERSION: u8 = 1;
+
+    /// `SharedMemorySharedDataHeader`. The size is fixed to 8 bytes on all architectures.
+    #[repr(C, align(8))]
+    struct SharedDataHeader {
+        shared_memory_type: u8,
+        version: u8,
+    }
+
+    /// `NamedMutexSharedData` with `NAMED_MUTEX_USE_PTHREAD_MUTEX`
+    #[repr(C)]
+    struct NamedMutexSharedData {
+        lock: libc::pthread_mutex_t,
+        lock_owner_process_id: u32,
+        lock_owner_thread_id: usize,
+        // used only on platforms without robust pthread mutex
+        _is_abandoned: bool,
+    }
+
+    #[repr(C)]
+    struct SharedMemory {
+        header: SharedDataHeader,
+        data: NamedMutexSharedData,
+    }
+
+    /// The size of the shared memory file like `SharedMemoryProcessDataHeader::GetTotalByteCount`,
+    /// which rounds the header and the data up to the virtual page size.
+    fn shared_memory_size() -> usize {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        size_of::<SharedMemory>().next_multiple_of(page_size)
+    }
+
+    /// The mapped shared memory, unmapped on drop.
+    /// The memory is shared with other processes and only accessed with pthread mutex functions.
+    struct SharedMemoryMapping(*mut SharedMemory);
+    unsafe impl Send for SharedMemoryMapping {}
+    unsafe impl Sync for SharedMemoryMapping {}
+
+    impl Drop for SharedMemoryMapping {
+        fn drop(&mut self) {
+            unsafe { libc::munmap(self.0 as *mut _, shared_memory_size()) };
+        }
+    }
+
+    pub(super) struct SharedMutexImpl {
+        path: PathBuf,
+        // the locking thread may outlive this struct if lock future is dropped, so shared with Arc
+        memory: Arc<SharedMemoryMapping>,
+        // the shared lock on the file indicates this file is in use
+        file: File,
+    }
+
+    pub(super) struct MutexGuardImpl {
+        wait_sender: std::sync::mpsc::SyncSender<()>,
+        release_end_receiver: Mutex<std::sync::mpsc::Receiver<()>>,
+    }
+
+    /// Returns the `.dotnet` directory in temp directory like `SharedMemoryHelpers` does.
+    fn runtime_temp_directory() -> PathBuf {
+        std::env::var_os("TMPDIR")
+            .filter(|x| !x.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("/tmp"))
+            .join(".dotnet")
+    }
+
+    fn ensure_directory_exists(path: &Path) -> io::Result<()> {
+        match std::fs::DirBuilder::new().mode(0o777).create(path) {
+            Ok(()) => {
+                // mode is affected by umask, so set permissions explicitly like runtime does
+                std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o777))
+            }
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn flock(file: &File, operation: libc::c_int) -> io::Result<()> {
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+                return Ok(());
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != io::ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+
+    fn pthread_result(result: libc::c_int) -> io::Result<()> {
+        match result {
+            0 => Ok(()),
+            error => Err(io::Error::from_raw_os_error(error)),
+        }
+    }
+
+    /// Locks the creation / deletion lock of shared memory files.
+    ///
+    /// The runtime uses exclusive `flock` on the shared memory directory.
+    fn creation_deletion_lock(shm_directory: &Path) -> io::Result<File> {
+        let directory = File::open(shm_directory)?;
+        flock(&directory, libc::LOCK_EX)?;
+        Ok(directory)
+    }
+
+    /// Initializes the newly created shared memory like `NamedMutexSharedData` constructor.
+    unsafe fn initialize_shared_memory(memory: *mut SharedMemory) -> io::Result<()> {
+        unsafe {
+            (*memory).header.shared_memory_type = SHARED_MEMORY_TYPE_MUTEX;
+            (*memory).header.version = SHARED_MEMORY_MUTEX_VERSION;
+
+            let mut attr = std::mem::MaybeUninit::<libc::pthread_mutexattr_t>::uninit();
+            pthread_result(libc::pthread_mutexattr_init(attr.as_mut_ptr()))?;
+            let result = (|| {
+                pthread_result(libc::pthread_mutexattr_setpshared(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_PROCESS_SHARED,
+                ))?;
+                pthread_result(libc::pthread_mutexattr_setrobust(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_MUTEX_ROBUST,
+                ))?;
+                pthread_result(libc::pthread_mutexattr_settype(
+                    attr.as_mut_ptr(),
+                    libc::PTHREAD_MUTEX_RECURSIVE,
+                ))?;
+                pthread_result(libc::pthread_mutex_init(
+                    &raw mut (*memory).data.lock,
+                    attr.as_ptr(),
+                ))
+            })();
+            libc::pthread_mutexattr_destroy(attr.as_mut_ptr());
+            result
+        }
+    }
+
+    impl SharedMutexImpl {
+        pub async fn new(name: &OsStr) -> io::Result<Self> {
+            // name is validated to start with Global\
+            let name = name.as_bytes()[b"Global\\".len()..].to_vec();
+
+            match tokio::task::spawn_blocking(move || Self::open(OsStr::from_bytes(&name))).await {
+                Ok(result) => result,
+                Err(_) => Err(io::Error::other("background task failed")),
+            }
+        }
+
+        fn open(name: &OsStr) -> io::Result<Self> {
+            let runtime_temp = runtime_temp_directory();
+            let shm_directory = runtime_temp.join("shm");
+            let global_directory = shm_directory.join("global");
+            let path = global_directory.join(name);
+
+            ensure_directory_exists(&runtime_temp)?;
+            ensure_directory_exists(&shm_directory)?;
+
+            let _creation_lock = creation_deletion_lock(&shm_directory)?;
+
+            ensure_directory_exists(&global_directory)?;
+
+            let (file, created) = match std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .mode(0o666)
+                .open(&path)
+            {
+                Ok(file) => {
+                    file.set_permissions(std::fs::Permissions::from_mode(0o666))?;
+                    (file, true)
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => (
+                    std::fs::OpenOptions::new()
+                        .read(true)
+                        .write(true)
+                        .open(&path)?,
+                    false,
+                ),
+                Err(e) => return Err(e),
+            };
+
+            let size = shared_memory_size();
+            let result = (|| {
+                if created {
+                    file.set_len(size as u64)?;
+                } else if file.metadata()?.len() < size as u64 {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "The shared memory file is too small",
+                    ));
+                }
+
+                // the shared lock indicates that the file is in use
+                flock(&file, libc::LOCK_SH)?;
+
+                let memory = unsafe {
+                    libc::mmap(
+                        std::ptr::null_mut(),
+                        size,
+                        libc::PROT_READ | libc::PROT_WRITE,
+                        libc::MAP_SHARED,
+                        file.as_raw_fd(),
+                        0,
+                    )
+                };
+                if memory == libc::MAP_FAILED {
+                    return Err(io::Error::last_os_error());
+                }
+                let memory = SharedMemoryMapping(memory as *mut SharedMemory);
+
+                unsafe {
+                    if created {
+                        initialize_shared_memory(memory.0)?;
+                    } else if (*memory.0).header.shared_memory_type != SHARED_MEMORY_TYPE_MUTEX
+                        || (*memory.0).header.version != SHARED_MEMORY_MUTEX_VERSION
+                    {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "The shared memory file is not a compatible mutex",
+                        ));
+                    }
+                }
+
+                Ok(memory)
+            })();
+
+            match result {
+                Ok(memory) => Ok(Self {
+                    path,
+                    memory: Arc::new(memory),
+                    file,
+                }),
+                Err(e) => {
+                    if created {
+                        std::fs::remove_file(&path).ok();
+                    }
+                    Err(e)
+                }
+            }
+        }
+
+        pub async fn lock(&self) -> io::Result<MutexGuardImpl> {
+            let (lock_sender, result_receiver) = oneshot::channel::<io::Result<()>>();
+            let (wait_sender, wait_receiver) = std::sync::mpsc::sync_channel::<()>(1);
+            let (release_end_sender, release_end_receiver) = std::sync::mpsc::channel::<()>();
+
+            let memory = self.memory.clone();
+
+            // create thread for locking and releasing since
+            // pthread mutex must be unlocked by the thread locked it.
+            std::thread::spawn(move || {
+                let data = unsafe { &raw mut (*memory.0).data };
+                unsafe {
+                    match libc::pthread_mutex_lock(&raw mut (*data).lock) {
+                        0 => {}
+                        libc::EOWNERDEAD => {
+                            // the previous owner died with lock kept. make it usable again and report
+                            libc::pthread_mutex_consistent(&raw mut (*data).lock);
+                            (*data).lock_owner_process_id = 0;
+                            (*data).lock_owner_thread_id = 0;
+                            libc::pthread_mutex_unlock(&raw mut (*data).lock);
+                            lock_sender.send(Err(io::Error::new(io::ErrorKind::Deadlock, "The mutex is held by another thread and the thread exited with lock in kept."))).ok();
+                            return;
+                        }
+                        error => {
+                            lock_sender
+                                .send(Err(io::Error::from_raw_os_error(error)))
+                                .ok();
+                            return;
+                        }
+                    }
+
+                    (*data).lock_owner_process_id = std::process::id();
+                    (*data).lock_owner_thread_id = libc::gettid() as usize;
+                }
+
+                // if the lock future is dropped before locked, we release the lock immediately
+                if lock_sender.send(Ok(())).is_ok() {
+                    wait_receiver.recv().ok();
+                }
+
+                unsafe {
+                    (*data).lock_owner_process_id = 0;
+                    (*data).lock_owner_thread_id = 0;
+                    libc::pthread_mutex_unlock(&raw mut (*data).lock);
+                }
+
+                release_end_sender.send(()).ok();
+            });
+
+            result_receiver
+                .await
+                .map_err(|_| io::Error::other("background thread failed"))??;
+
+            Ok(MutexGuardImpl {
+                wait_sender,
+                release_end_receiver: Mutex::new(release_end_receiver),
+            })
+        }
+    }
+
+    impl Drop for SharedMutexImpl {
+        fn drop(&mut self) {
+            // like runtime, the last user of the shared memory file removes it.
+            let Some(shm_directory) = self.path.parent().and_then(Path::parent) else {
+                return;
+            };
+            let Ok(_deletion_lock) = creation_deletion_lock(shm_directory) else {
+                return;
+            };
+            if flock(&self.file, libc::LOCK_EX | libc::LOCK_NB).is_ok() {
+                std::fs::remove_file(&self.path).ok();
+            }
+        }
+    }
+
+    impl Drop for MutexGuardImpl {
+        fn drop(&mut self) {
+            self.wait_sender.send(()).ok();
+            self.release_end_receiver.get_mut().unwrap().recv().ok();
+        }
+    }
+}
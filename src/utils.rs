@@ -13,58 +13,13 @@ use std::fmt::{Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
 use std::ops::{Deref, Index, IndexMut, Neg};
+use std::sync::Arc;
 
-// TODO: Implement the CompareOptions struct
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct CompareOptions(pub i32);
+mod collation;
+mod random;
 
-#[allow(dead_code)]
-impl CompareOptions {
-    pub const IGNORE_CASE: CompareOptions = CompareOptions(1);
-    pub const IGNORE_KANA_TYPE: CompareOptions = CompareOptions(8);
-    pub const IGNORE_NON_SPACE: CompareOptions = CompareOptions(2);
-    pub const IGNORE_SYMBOLS: CompareOptions = CompareOptions(4);
-    pub const IGNORE_WIDTH: CompareOptions = CompareOptions(16);
-    pub const ORDINAL: CompareOptions = CompareOptions(1073741824);
-    pub const STRING_SORT: CompareOptions = CompareOptions(536870912);
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct Collation {
-    pub lcid: i32,
-    pub sort_options: CompareOptions,
-}
-
-impl Default for Collation {
-    fn default() -> Self {
-        Collation {
-            lcid: 127,                             // invariant
-            sort_options: CompareOptions::ORDINAL, // we only support ordinal order
-        }
-    }
-}
-
-const _: () = {
-    // static aserts
-    assert!(size_of::<Collation>() == size_of::<u64>());
-};
-
-impl Collation {
-    pub fn new(lcid: i32, sort_options: CompareOptions) -> Self {
-        Collation { lcid, sort_options }
-    }
-
-    pub(crate) fn compare(&self, left: &bson::Value, right: &bson::Value) -> Ordering {
-        use bson::Value::*;
-        match (left, right) {
-            (String(l), String(r)) => l.cmp_cs_ordinal(r), // TODO: compare with collation settings
-            (l, r) => l.total_cmp(r),
-        }
-    }
-
-    //    pub(crate) fn sql_like(&self, left: &str, right: &str) -> bool {
-    //    }
-}
+pub use collation::{Collation, CompareOptions};
+pub(crate) use random::Randomizer;
 
 #[repr(transparent)]
 pub struct BufferSlice {
@@ -352,7 +307,6 @@ impl BufferSlice {
         Self::new_mut(&mut self.buffer[offset..][..count])
     }
 
-    #[allow(dead_code)]
     pub fn as_bytes_mut(&mut self) -> &mut [u8] {
         &mut self.buffer
     }
@@ -683,24 +637,121 @@ pub(crate) mod checker {
     pub(crate) fn check_sync_send<'a, T: Send + Sync + 'a>(_: T) {}
 }
 
+/// The number of entries in a chunk of [`KeyArena`].
+const ARENA_CHUNK_SIZE: usize = 256;
+
+/// The arena of values identified by [`ArenaKey`].
+///
+/// Each value is reference-counted, and stored in reference-counted chunks. Both are copied on write.
+/// Therefore, cloning the arena only copies pointers to chunks, and modifying an entry
+/// of a chunk shared with clones copies the pointers in the chunk and the entry only.
 #[derive(Debug)]
-pub(crate) struct KeyArena<T>(slab::Slab<T>);
+pub(crate) struct KeyArena<T> {
+    chunks: Vec<Arc<Vec<Option<Arc<T>>>>>,
+    /// The keys of freed entries. The last freed key is reused first
+    free_keys: Vec<usize>,
+    len: usize,
+}
+
+impl<T> Clone for KeyArena<T> {
+    fn clone(&self) -> Self {
+        Self {
+            chunks: self.chunks.clone(),
+            free_keys: self.free_keys.clone(),
+            len: self.len,
+        }
+    }
+}
 
 impl<T> KeyArena<T> {
     pub fn new() -> Self {
-        Self(slab::Slab::new())
+        Self {
+            chunks: Vec::new(),
+            free_keys: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Returns `None` if the key is already freed
+    pub fn get(&self, key: ArenaKey<T>) -> Option<&T> {
+        self.chunks
+            .get(key.0 / ARENA_CHUNK_SIZE)?
+            .get(key.0 % ARENA_CHUNK_SIZE)?
+            .as_deref()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns all keys and values in this arena.
+    pub fn iter(&self) -> impl Iterator<Item = (ArenaKey<T>, &T)> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .flat_map(|(chunk_index, chunk)| {
+                chunk.iter().enumerate().filter_map(move |(index, value)| {
+                    let key = ArenaKey(chunk_index * ARENA_CHUNK_SIZE + index, PhantomData);
+                    value.as_deref().map(|value| (key, value))
+                })
+            })
     }
+}
 
+impl<T: Clone> KeyArena<T> {
     pub fn alloc(&mut self, value: T) -> ArenaKey<T> {
-        ArenaKey(self.0.insert(value), PhantomData)
+        self.len += 1;
+
+        if let Some(key) = self.free_keys.pop() {
+            *self.entry_mut(key) = Some(Arc::new(value));
+            return ArenaKey(key, PhantomData);
+        }
+
+        if self
+            .chunks
+            .last()
+            .is_none_or(|chunk| chunk.len() == ARENA_CHUNK_SIZE)
+        {
+            self.chunks
+                .push(Arc::new(Vec::with_capacity(ARENA_CHUNK_SIZE)));
+        }
+
+        let chunk_index = self.chunks.len() - 1;
+        let chunk = Arc::make_mut(&mut self.chunks[chunk_index]);
+        chunk.push(Some(Arc::new(value)));
+        ArenaKey(
+            chunk_index * ARENA_CHUNK_SIZE + chunk.len() - 1,
+            PhantomData,
+        )
     }
 
     pub fn free(&mut self, key: ArenaKey<T>) -> T {
-        self.0.remove(key.0)
+        let value = self.entry_mut(key.0).take().expect("invalid key");
+        self.free_keys.push(key.0);
+        self.len -= 1;
+        Arc::unwrap_or_clone(value)
     }
 
-    pub(crate) fn len(&self) -> usize {
-        self.0.len()
+    pub fn get_mut(&mut self, key: ArenaKey<T>) -> Option<&mut T> {
+        let chunk = self.chunks.get_mut(key.0 / ARENA_CHUNK_SIZE)?;
+        if chunk.get(key.0 % ARENA_CHUNK_SIZE)?.is_none() {
+            return None;
+        }
+        let value = Arc::make_mut(chunk)[key.0 % ARENA_CHUNK_SIZE].as_mut()?;
+        Some(Arc::make_mut(value))
+    }
+
+    /// Returns mutable references to all values, copying all shared chunks and values.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.chunks
+            .iter_mut()
+            .flat_map(|chunk| Arc::make_mut(chunk).iter_mut())
+            .flatten()
+            .map(Arc::make_mut)
+    }
+
+    fn entry_mut(&mut self, key: usize) -> &mut Option<Arc<T>> {
+        &mut Arc::make_mut(&mut self.chunks[key / ARENA_CHUNK_SIZE])[key % ARENA_CHUNK_SIZE]
     }
 }
 
@@ -708,13 +759,13 @@ impl<T> Index<ArenaKey<T>> for KeyArena<T> {
     type Output = T;
 
     fn index(&self, index: ArenaKey<T>) -> &Self::Output {
-        &self.0[index.0]
+        self.get(index).expect("invalid key")
     }
 }
 
-impl<T> IndexMut<ArenaKey<T>> for KeyArena<T> {
+impl<T: Clone> IndexMut<ArenaKey<T>> for KeyArena<T> {
     fn index_mut(&mut self, index: ArenaKey<T>) -> &mut Self::Output {
-        &mut self.0[index.0]
+        self.get_mut(index).expect("invalid key")
     }
 }
 
@@ -793,3 +844,23 @@ impl<T> IntoOk<T> for Result<T, Infallible> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arena_copy_on_write_shares_other_values() {
+        let mut arena = KeyArena::new();
+        let first = arena.alloc("first".to_string());
+        let second = arena.alloc("second".to_string());
+
+        let snapshot = arena.clone();
+        arena[first].push('!');
+
+        assert_eq!(arena[first], "first!");
+        assert_eq!(snapshot[first], "first");
+        // values not modified are not copied
+        assert!(std::ptr::eq(&arena[second], &snapshot[second]));
+    }
+}
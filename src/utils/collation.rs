@@ -0,0 +1,480 @@
+//! String comparison compatible with `CompareInfo.Compare` of .NET.
+//!
+//! Since .NET 5, culture-aware comparison is implemented with ICU on all platforms.
+//! With `icu-collation` feature, we compare strings with ICU4X collator configured
+//! the same way as `CloneCollatorWithOptions` in `pal_collation.c` of .NET runtime does.
+//! Without the feature, only ordinal comparisons are supported.
+//!
+//! Kana type and width are not exact. .NET builds the collator with custom tailoring rules
+//! for them, but ICU4X cannot apply custom rules.
+//! With `IgnoreKanaType` and `IgnoreWidth`, we fold each character (hiragana to katakana,
+//! fullwidth and halfwidth forms to the normal width) before comparison.
+//! Without them, .NET keeps kana type and width distinct even with `IgnoreCase` or `IgnoreNonSpace`,
+//! which makes ICU ignore them, so we break ties of such strings with kana type and width.
+//! This keeps such strings distinct, but they may be ordered differently from .NET against other strings.
+//! Characters not covered by the folding, like halfwidth hangul or composed voiced kana,
+//! may also be ordered differently from .NET.
+
+use super::CSharpStringUtils;
+use crate::bson;
+use crate::bson::TotalOrd;
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::BitOr;
+
+/// The `CompareOptions` of .NET.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CompareOptions(pub i32);
+
+impl CompareOptions {
+    pub const NONE: CompareOptions = CompareOptions(0);
+    pub const IGNORE_CASE: CompareOptions = CompareOptions(1);
+    pub const IGNORE_KANA_TYPE: CompareOptions = CompareOptions(8);
+    pub const IGNORE_NON_SPACE: CompareOptions = CompareOptions(2);
+    pub const IGNORE_SYMBOLS: CompareOptions = CompareOptions(4);
+    pub const IGNORE_WIDTH: CompareOptions = CompareOptions(16);
+    pub const ORDINAL_IGNORE_CASE: CompareOptions = CompareOptions(268435456);
+    pub const ORDINAL: CompareOptions = CompareOptions(1073741824);
+    pub const STRING_SORT: CompareOptions = CompareOptions(536870912);
+
+    /// Returns true if all flags in `other` are set.
+    pub const fn contains(self, other: CompareOptions) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+/// Formats like `ToString` of .NET enum, e.g. `IgnoreCase, IgnoreWidth`
+impl Display for CompareOptions {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        const NAMES: &[(CompareOptions, &str)] = &[
+            (CompareOptions::IGNORE_CASE, "IgnoreCase"),
+            (CompareOptions::IGNORE_NON_SPACE, "IgnoreNonSpace"),
+            (CompareOptions::IGNORE_SYMBOLS, "IgnoreSymbols"),
+            (CompareOptions::IGNORE_KANA_TYPE, "IgnoreKanaType"),
+            (CompareOptions::IGNORE_WIDTH, "IgnoreWidth"),
+            (CompareOptions::ORDINAL_IGNORE_CASE, "OrdinalIgnoreCase"),
+            (CompareOptions::STRING_SORT, "StringSort"),
+            (CompareOptions::ORDINAL, "Ordinal"),
+        ];
+
+        if self.0 == 0 {
+            return f.write_str("None");
+        }
+
+        let known = NAMES.iter().fold(0, |acc, (flag, _)| acc | flag.0);
+        if self.0 & !known != 0 {
+            // unknown flags are formatted as number like .NET does
+            return write!(f, "{}", self.0);
+        }
+
+        let mut first = true;
+        for (flag, name) in NAMES {
+            if self.contains(*flag) {
+                if !first {
+                    f.write_str(", ")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl BitOr for CompareOptions {
+    type Output = CompareOptions;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        CompareOptions(self.0 | rhs.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Collation {
+    pub lcid: i32,
+    pub sort_options: CompareOptions,
+}
+
+/// Formats like `Collation.ToString` of LiteDB, e.g. `en-US/IgnoreCase`.
+///
+/// The culture name of the invariant culture is empty, and unknown cultures are formatted as LCID.
+impl Display for Collation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match culture_name(self.lcid) {
+            Some(name) => write!(f, "{name}/{}", self.sort_options),
+            None => write!(f, "{}/{}", self.lcid, self.sort_options),
+        }
+    }
+}
+
+impl Default for Collation {
+    fn default() -> Self {
+        Collation {
+            lcid: 127,                             // invariant
+            sort_options: CompareOptions::ORDINAL, // ordinal order is always supported
+        }
+    }
+}
+
+const _: () = {
+    // static aserts
+    assert!(size_of::<Collation>() == size_of::<u64>());
+};
+
+impl Collation {
+    pub fn new(lcid: i32, sort_options: CompareOptions) -> Self {
+        Collation { lcid, sort_options }
+    }
+
+    /// Returns true if strings are compared in the same order as LiteDB on .NET does.
+    ///
+    /// Ordinal comparisons are always supported.
+    /// Culture-aware comparisons need `icu-collation` feature, and only the invariant culture
+    /// and some common cultures are supported.
+    /// Strings are compared in ordinal order with unsupported collations.
+    pub fn is_supported(&self) -> bool {
+        match self.sort_options {
+            CompareOptions::ORDINAL | CompareOptions::ORDINAL_IGNORE_CASE => true,
+            #[cfg(feature = "icu-collation")]
+            _ => icu::with_collator(*self, |collator| collator.is_some()),
+            #[cfg(not(feature = "icu-collation"))]
+            _ => false,
+        }
+    }
+
+    pub(crate) fn compare(&self, left: &bson::Value, right: &bson::Value) -> Ordering {
+        use bson::Value::*;
+        match (left, right) {
+            (String(l), String(r)) => self.compare_str(l, r),
+            (l, r) => l.total_cmp(r),
+        }
+    }
+
+    pub(crate) fn compare_str(&self, left: &str, right: &str) -> Ordering {
+        match self.sort_options {
+            CompareOptions::ORDINAL => left.cmp_cs_ordinal(right),
+            CompareOptions::ORDINAL_IGNORE_CASE => left
+                .to_upper_invariant()
+                .cmp_cs_ordinal(&right.to_upper_invariant()),
+            _ => {
+                #[cfg(feature = "icu-collation")]
+                return icu::with_collator(*self, |collator| match collator {
+                    Some(collator) => icu::compare(collator, self.sort_options, left, right),
+                    // unsupported collation
+                    None => left.cmp_cs_ordinal(right),
+                });
+                // unsupported collation
+                #[cfg(not(feature = "icu-collation"))]
+                left.cmp_cs_ordinal(right)
+            }
+        }
+    }
+
+    //    pub(crate) fn sql_like(&self, left: &str, right: &str) -> bool {
+    //    }
+}
+
+#[cfg(feature = "icu-collation")]
+mod icu {
+    use super::{Collation, CompareOptions, culture_name};
+    use icu_collator::{
+        AlternateHandling, CaseLevel, Collator, CollatorOptions, MaxVariable, Strength,
+    };
+    use std::cell::RefCell;
+    use std::cmp::Ordering;
+
+    thread_local! {
+        /// The collators created for each collation. `None` if the collation is not supported.
+        ///
+        /// Usually only a few collations are used, so linear search is faster than hashing
+        static COLLATORS: RefCell<Vec<(Collation, Option<Collator>)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    /// Calls `f` with the collator for the collation, or `None` if the collation is not supported
+    pub(super) fn with_collator<R>(
+        collation: Collation,
+        f: impl FnOnce(Option<&Collator>) -> R,
+    ) -> R {
+        COLLATORS.with(|collators| {
+            if !collators.borrow().iter().any(|(x, _)| *x == collation) {
+                let collator = create_collator(collation);
+                collators.borrow_mut().push((collation, collator));
+            }
+
+            let collators = collators.borrow();
+            let (_, collator) = collators.iter().find(|(x, _)| *x == collation).unwrap();
+            f(collator.as_ref())
+        })
+    }
+
+    fn create_collator(collation: Collation) -> Option<Collator> {
+        let options = collation.sort_options;
+
+        let supported = CompareOptions::IGNORE_CASE
+            | CompareOptions::IGNORE_NON_SPACE
+            | CompareOptions::IGNORE_SYMBOLS
+            | CompareOptions::IGNORE_KANA_TYPE
+            | CompareOptions::IGNORE_WIDTH
+            | CompareOptions::STRING_SORT; // ICU based comparison ignores StringSort
+        if options.0 & !supported.0 != 0 {
+            return None;
+        }
+
+        let mut strength = Strength::Tertiary;
+        if options.contains(CompareOptions::IGNORE_CASE) {
+            strength = Strength::Secondary;
+        }
+        if options.contains(CompareOptions::IGNORE_NON_SPACE) {
+            strength = Strength::Primary;
+        }
+
+        let mut icu_options = CollatorOptions::new();
+        icu_options.strength = Some(strength);
+
+        // case differs at tertiary level, so we need case level to keep case sensitivity with lower strength
+        if strength != Strength::Tertiary && !options.contains(CompareOptions::IGNORE_CASE) {
+            icu_options.case_level = Some(CaseLevel::On);
+        }
+
+        // shifted alternate handling only ignores punctuation by default,
+        // but IgnoreSymbols also ignores symbols and currency symbols
+        if options.contains(CompareOptions::IGNORE_SYMBOLS) {
+            icu_options.alternate_handling = Some(AlternateHandling::Shifted);
+            icu_options.max_variable = Some(MaxVariable::Currency);
+        }
+
+        // invariant culture uses root collation
+        let locale = match culture_name(collation.lcid)? {
+            "" => "und",
+            name => name,
+        };
+
+        Collator::try_new(&locale.parse().ok()?, icu_options).ok()
+    }
+
+    pub(super) fn compare(
+        collator: &Collator,
+        options: CompareOptions,
+        left: &str,
+        right: &str,
+    ) -> Ordering {
+        // kana type and width differ at tertiary level in ICU, so we fold them before comparison.
+        // this approximates the tailoring rules of .NET; see the module documentation.
+        let ignore_kana = options.contains(CompareOptions::IGNORE_KANA_TYPE);
+        let ignore_width = options.contains(CompareOptions::IGNORE_WIDTH);
+        let fold = |str: &str| -> String {
+            str.chars()
+                .map(|c| if ignore_width { fold_width(c) } else { c })
+                .map(|c| if ignore_kana { fold_kana(c) } else { c })
+                .collect()
+        };
+
+        if left.is_ascii() && right.is_ascii() {
+            return collator.compare(left, right);
+        }
+
+        let ordering = if !ignore_kana && !ignore_width {
+            collator.compare(left, right)
+        } else {
+            collator.compare(&fold(left), &fold(right))
+        };
+
+        // with IgnoreCase or IgnoreNonSpace, ICU compares at secondary or lower strength
+        // so kana type and width are ignored even if they are not requested to be ignored.
+        // .NET keeps them distinct with custom rules, so we break the tie with them.
+        let lower_strength = options.contains(CompareOptions::IGNORE_CASE)
+            || options.contains(CompareOptions::IGNORE_NON_SPACE);
+        if ordering != Ordering::Equal || !lower_strength || ignore_kana && ignore_width {
+            return ordering;
+        }
+
+        let variants = |str: &str| {
+            str.chars()
+                .map(|c| {
+                    let kana = if ignore_kana { 0 } else { kana_type(c) };
+                    let width = if ignore_width { 0 } else { width_variant(c) };
+                    (kana, width)
+                })
+                .filter(|&variant| variant != (0, 0))
+                .collect::<Vec<_>>()
+        };
+        variants(left).cmp(&variants(right))
+    }
+
+    /// Returns 1 for hiragana, 2 for katakana, and 0 for other characters.
+    ///
+    /// Hiragana comes before katakana like the custom rules of .NET
+    fn kana_type(c: char) -> u8 {
+        match c {
+            '\u{3041}'..='\u{3096}' | '\u{309D}'..='\u{309E}' => 1,
+            '\u{30A1}'..='\u{30F6}' | '\u{30FD}'..='\u{30FE}' | '\u{FF66}'..='\u{FF9D}' => 2,
+            _ => 0,
+        }
+    }
+
+    /// Returns 1 for fullwidth or halfwidth variant of characters, and 0 for other characters.
+    ///
+    /// The normal width comes before the variants like the custom rules of .NET
+    fn width_variant(c: char) -> u8 {
+        (fold_width(c) != c) as u8
+    }
+
+    /// Converts hiragana to katakana
+    fn fold_kana(c: char) -> char {
+        match c {
+            '\u{3041}'..='\u{3096}' | '\u{309D}'..='\u{309E}' => {
+                char::from_u32(c as u32 + 0x60).unwrap()
+            }
+            _ => c,
+        }
+    }
+
+    /// Converts fullwidth ASCII and halfwidth katakana to the normal width
+    fn fold_width(c: char) -> char {
+        const HALFWIDTH_KATAKANA: &[char; 0x3F] = &[
+            '。', '「', '」', '、', '・', 'ヲ', 'ァ', 'ィ', 'ゥ', 'ェ', 'ォ', 'ャ', 'ュ', 'ョ',
+            'ッ', 'ー', 'ア', 'イ', 'ウ', 'エ', 'オ', 'カ', 'キ', 'ク', 'ケ', 'コ', 'サ', 'シ',
+            'ス', 'セ', 'ソ', 'タ', 'チ', 'ツ', 'テ', 'ト', 'ナ', 'ニ', 'ヌ', 'ネ', 'ノ', 'ハ',
+            'ヒ', 'フ', 'ヘ', 'ホ', 'マ', 'ミ', 'ム', 'メ', 'モ', 'ヤ', 'ユ', 'ヨ', 'ラ', 'リ',
+            'ル', 'レ', 'ロ', 'ワ', 'ン', '\u{3099}', '\u{309A}',
+        ];
+
+        match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFF01 + 0x21).unwrap(),
+            '\u{FF61}'..='\u{FF9F}' => HALFWIDTH_KATAKANA[c as usize - 0xFF61],
+            _ => c,
+        }
+    }
+}
+
+/// Returns the name of the culture for the LCID
+fn culture_name(lcid: i32) -> Option<&'static str> {
+    Some(match lcid {
+        127 => "", // invariant culture
+        1028 => "zh-TW",
+        1029 => "cs-CZ",
+        1030 => "da-DK",
+        1031 => "de-DE",
+        1032 => "el-GR",
+        1033 => "en-US",
+        1035 => "fi-FI",
+        1036 => "fr-FR",
+        1038 => "hu-HU",
+        1040 => "it-IT",
+        1041 => "ja-JP",
+        1042 => "ko-KR",
+        1043 => "nl-NL",
+        1044 => "nb-NO",
+        1045 => "pl-PL",
+        1046 => "pt-BR",
+        1049 => "ru-RU",
+        1053 => "sv-SE",
+        1055 => "tr-TR",
+        1058 => "uk-UA",
+        1066 => "vi-VN",
+        2052 => "zh-CN",
+        2057 => "en-GB",
+        2070 => "pt-PT",
+        3076 => "zh-HK",
+        3082 => "es-ES",
+        4105 => "en-CA",
+        3081 => "en-AU",
+        _ => return None,
+    })
+}
+
+#[cfg(all(test, feature = "icu-collation"))]
+mod tests {
+    use super::*;
+
+    fn invariant(options: CompareOptions) -> Collation {
+        Collation::new(127, options)
+    }
+
+    #[test]
+    fn ignore_case() {
+        let collation = invariant(CompareOptions::IGNORE_CASE);
+        assert!(collation.is_supported());
+        assert_eq!(collation.compare_str("abc", "ABC"), Ordering::Equal);
+        assert_eq!(collation.compare_str("a", "B"), Ordering::Less);
+        assert_eq!(collation.compare_str("B", "a"), Ordering::Greater);
+        assert_ne!(collation.compare_str("a", "á"), Ordering::Equal);
+    }
+
+    #[test]
+    fn case_sensitive() {
+        let collation = invariant(CompareOptions::NONE);
+        // lower case comes first, but the case is less significant than letters
+        assert_eq!(collation.compare_str("a", "A"), Ordering::Less);
+        assert_eq!(collation.compare_str("A", "b"), Ordering::Less);
+        assert_eq!(collation.compare_str("a", "b"), Ordering::Less);
+    }
+
+    #[test]
+    fn ignore_non_space() {
+        let collation = invariant(CompareOptions::IGNORE_NON_SPACE);
+        assert_eq!(collation.compare_str("café", "cafe"), Ordering::Equal);
+        assert_ne!(collation.compare_str("Cafe", "cafe"), Ordering::Equal);
+
+        let collation = invariant(CompareOptions::IGNORE_NON_SPACE | CompareOptions::IGNORE_CASE);
+        assert_eq!(collation.compare_str("Café", "cafe"), Ordering::Equal);
+    }
+
+    #[test]
+    fn ignore_symbols() {
+        let collation = invariant(CompareOptions::IGNORE_SYMBOLS);
+        assert_eq!(collation.compare_str("a-b c", "abc"), Ordering::Equal);
+        assert_eq!(collation.compare_str("$1", "1"), Ordering::Equal);
+        assert_ne!(
+            invariant(CompareOptions::NONE).compare_str("a-b", "ab"),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn ignore_kana_type_and_width() {
+        let kana = invariant(CompareOptions::IGNORE_KANA_TYPE);
+        assert_eq!(kana.compare_str("ひらがな", "ヒラガナ"), Ordering::Equal);
+        assert_ne!(kana.compare_str("ｶﾀｶﾅ", "カタカナ"), Ordering::Equal);
+
+        let width = invariant(CompareOptions::IGNORE_WIDTH);
+        assert_eq!(width.compare_str("ｶﾀｶﾅ", "カタカナ"), Ordering::Equal);
+        assert_eq!(width.compare_str("ＡＢＣ", "ABC"), Ordering::Equal);
+        assert_ne!(width.compare_str("ひらがな", "ヒラガナ"), Ordering::Equal);
+
+        let none = invariant(CompareOptions::NONE);
+        assert_ne!(none.compare_str("ＡＢＣ", "ABC"), Ordering::Equal);
+    }
+
+    #[test]
+    fn kana_type_and_width_with_ignore_case() {
+        let collation = invariant(CompareOptions::IGNORE_CASE);
+        assert_eq!(collation.compare_str("ひ", "ヒ"), Ordering::Less);
+        assert_eq!(collation.compare_str("ヒ", "ひ"), Ordering::Greater);
+        assert_eq!(collation.compare_str("カ", "ｶ"), Ordering::Less);
+        assert_eq!(collation.compare_str("ＡＢＣ", "abc"), Ordering::Greater);
+        assert_eq!(
+            collation.compare_str("ひらがなA", "ひらがなa"),
+            Ordering::Equal
+        );
+
+        let kana = invariant(CompareOptions::IGNORE_CASE | CompareOptions::IGNORE_KANA_TYPE);
+        assert_eq!(kana.compare_str("ひ", "ヒ"), Ordering::Equal);
+        assert_ne!(kana.compare_str("カ", "ｶ"), Ordering::Equal);
+
+        let both = invariant(
+            CompareOptions::IGNORE_NON_SPACE
+                | CompareOptions::IGNORE_KANA_TYPE
+                | CompareOptions::IGNORE_WIDTH,
+        );
+        assert_eq!(both.compare_str("か", "ｶﾞ"), Ordering::Equal);
+        assert_eq!(both.compare_str("ｶ", "か"), Ordering::Equal);
+    }
+
+    #[test]
+    fn unsupported() {
+        assert!(!Collation::new(0x7FFF, CompareOptions::IGNORE_CASE).is_supported());
+        assert!(Collation::new(0x7FFF, CompareOptions::ORDINAL).is_supported());
+    }
+}
@@ -0,0 +1,59 @@
+//! The source of random values used by [`LiteDBFile`](crate::file_io::LiteDBFile).
+//!
+//! By default, values are drawn from the thread-local RNG of `rand`.
+//! With a seed, values are drawn from the seeded [`ChaCha8Rng`] so that the same operations
+//! produce the same values.
+//! Unlike `StdRng`, the algorithm of [`ChaCha8Rng`] is fixed, so the values do not change
+//! when `rand` is updated.
+
+use crate::bson;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Randomizer {
+    seeded: Option<ChaCha8Rng>,
+}
+
+impl Randomizer {
+    pub fn seeded(seed: u64) -> Self {
+        Self {
+            seeded: Some(ChaCha8Rng::seed_from_u64(seed)),
+        }
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        match &mut self.seeded {
+            Some(rng) => rng.random(),
+            None => rand::random(),
+        }
+    }
+
+    /// Creates new ObjectId.
+    ///
+    /// With a seed, the ObjectId is filled with random bytes instead of the time and the process.
+    pub fn object_id(&mut self) -> bson::ObjectId {
+        match &mut self.seeded {
+            Some(rng) => bson::ObjectId::from_bytes(rng.random()),
+            None => bson::ObjectId::new(),
+        }
+    }
+
+    pub fn guid(&mut self) -> bson::Guid {
+        match &mut self.seeded {
+            Some(rng) => bson::Guid::from_random_bytes(rng.random()),
+            None => bson::Guid::new(),
+        }
+    }
+
+    /// Returns random bytes without advancing the state.
+    ///
+    /// This is for places where only shared reference is available, like serializing.
+    #[cfg_attr(not(feature = "encryption"), allow(dead_code))]
+    pub fn peek_bytes<const N: usize>(&self) -> [u8; N] {
+        match &self.seeded {
+            Some(rng) => rng.clone().random(),
+            None => rand::random(),
+        }
+    }
+}
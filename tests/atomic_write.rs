@@ -0,0 +1,173 @@
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+fn test_file() -> LiteDBFile {
+    let mut file = LiteDBFile::new();
+    for (name, expression) in [("a", "$.a"), ("b", "$.b")] {
+        file.ensure_index(
+            "test",
+            name,
+            BsonExpression::create(expression).unwrap(),
+            true,
+        )
+        .unwrap();
+    }
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "a" => "a1", "b" => "b1"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file
+}
+
+#[test]
+fn insert_document_atomically() {
+    let mut file = test_file();
+
+    // index "b" fails after the document is added to "_id" and "a" indexes
+    let result = file.insert(
+        "test",
+        vec![
+            document! {"_id" => 2, "a" => "a2", "b" => "b2"},
+            document! {"_id" => 3, "a" => "a3", "b" => "b1"},
+        ],
+        BsonAutoId::ObjectId,
+    );
+    assert!(result.is_err());
+
+    assert!(file.check_integrity().is_empty());
+    assert_eq!(file.get_all("test").count(), 2);
+    assert_eq!(file.get_by_index("test", "a", &"a3".into()).count(), 0);
+    assert!(LiteDBFile::check_file_integrity(&file.serialize()).is_empty());
+
+    // the _id can be used again
+    file.insert(
+        "test",
+        vec![document! {"_id" => 3, "a" => "a3", "b" => "b3"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    assert!(file.check_integrity().is_empty());
+}
+
+#[test]
+fn update_document_atomically() {
+    let mut file = test_file();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 2, "a" => "a2", "b" => "b2"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    let result = file.update(
+        "test",
+        vec![document! {"_id" => 2, "a" => "a3", "b" => "b1"}],
+    );
+    assert!(result.is_err());
+
+    assert!(file.check_integrity().is_empty());
+    let found = file
+        .get_by_index("test", "_id", &2.into())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![&document! {"_id" => 2, "a" => "a2", "b" => "b2"}]
+    );
+    assert_eq!(file.get_by_index("test", "a", &"a3".into()).count(), 0);
+    assert_eq!(file.get_by_index("test", "a", &"a2".into()).count(), 1);
+}
+
+#[test]
+fn atomic_batch() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    let result = file.atomically(|transaction| {
+        transaction.insert(
+            "test",
+            vec![
+                document! {"_id" => 2, "a" => "a2", "b" => "b2"},
+                document! {"_id" => 3, "a" => "a3", "b" => "b1"},
+            ],
+            BsonAutoId::ObjectId,
+        )
+    });
+    assert!(result.is_err());
+    assert_eq!(file.serialize(), original);
+
+    let result = file.atomically(|transaction| {
+        transaction.upsert(
+            "test",
+            vec![
+                document! {"_id" => 1, "a" => "a1", "b" => "b0"},
+                document! {"_id" => 2, "a" => "a2", "b" => "b2"},
+            ],
+            BsonAutoId::ObjectId,
+        )
+    });
+    assert_eq!(result.unwrap(), 1);
+    assert_eq!(file.get_all("test").count(), 2);
+    assert!(file.check_integrity().is_empty());
+
+    let snapshot = file.serialize();
+    let result = file.atomically(|transaction| {
+        transaction.update(
+            "test",
+            vec![
+                document! {"_id" => 1, "a" => "a9", "b" => "b9"},
+                document! {"_id" => 2, "a" => "a2", "b" => "b9"},
+            ],
+        )
+    });
+    assert!(result.is_err());
+    assert_eq!(file.serialize(), snapshot);
+    assert!(file.check_integrity().is_empty());
+}
+
+#[cfg(feature = "icu-collation")]
+#[test]
+fn update_key_equal_under_collation() {
+    // vcc.liteDb uses IgnoreCase collation
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+    file.ensure_index(
+        "test",
+        "other",
+        BsonExpression::create("$.other").unwrap(),
+        true,
+    )
+    .unwrap();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "key" => "abc", "other" => "def"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    let updated = file
+        .update(
+            "test",
+            vec![document! {"_id" => 1, "key" => "ABC", "other" => "DEF"}],
+        )
+        .unwrap();
+    assert_eq!(updated, 1);
+
+    assert!(file.check_integrity().is_empty());
+    let found = file
+        .get_by_index("test", "key", &"abc".into())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        found,
+        vec![&document! {"_id" => 1, "key" => "ABC", "other" => "DEF"}]
+    );
+    assert_eq!(file.get_by_index("test", "other", &"def".into()).count(), 1);
+}
@@ -0,0 +1,93 @@
+#![cfg(feature = "icu-collation")]
+
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, Collation, CompareOptions, LiteDBFile};
+
+#[test]
+fn keep_indexes_of_invariant_ignore_case() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let collation = file.collation();
+    assert_eq!(collation, Collation::new(127, CompareOptions::IGNORE_CASE));
+    assert!(collation.is_supported());
+
+    assert!(!file.drop_indexes_and_update_collation_if_collation_not_supported());
+    assert_eq!(file.collation(), collation);
+    assert!(file.check_integrity().is_empty());
+
+    // the index is searched with case ignored
+    let project = file.get_all("projects").next().unwrap().clone();
+    let path = project.get("Path").as_str().unwrap().to_uppercase();
+    let found = file
+        .get_by_index("projects", "Path", &path.into())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![&project]);
+}
+
+#[test]
+fn unique_index_under_ignore_case() {
+    let mut file = LiteDBFile::new();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+    file.insert(
+        "test",
+        vec![document! {"key" => "a"}, document! {"key" => "A"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    // "a" and "A" conflicts with IgnoreCase
+    let collation = file.collation();
+    assert!(
+        file.set_collation(Collation::new(127, CompareOptions::IGNORE_CASE))
+            .is_err()
+    );
+    assert_eq!(file.collation(), collation);
+    assert!(file.check_integrity().is_empty());
+
+    file.set_collation(Collation::new(127, CompareOptions::NONE))
+        .unwrap();
+    assert!(file.check_integrity().is_empty());
+    assert!(LiteDBFile::check_file_integrity(&file.serialize()).is_empty());
+}
+
+#[test]
+fn kana_type_and_width_are_distinct_under_ignore_case() {
+    let mut file = LiteDBFile::new();
+    file.set_collation(Collation::new(127, CompareOptions::IGNORE_CASE))
+        .unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+
+    // .NET does not ignore kana type and width with IgnoreCase
+    file.insert(
+        "test",
+        vec![
+            document! {"key" => "ひ"},
+            document! {"key" => "ヒ"},
+            document! {"key" => "ｶ"},
+            document! {"key" => "カ"},
+        ],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    assert!(
+        file.insert(
+            "test",
+            vec![document! {"key" => "a"}, document! {"key" => "A"}],
+            BsonAutoId::ObjectId
+        )
+        .is_err()
+    );
+    assert!(file.check_integrity().is_empty());
+}
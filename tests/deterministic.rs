@@ -0,0 +1,81 @@
+use vrc_get_litedb::bson::{DateTime, ObjectId};
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+fn build(seed: u64) -> LiteDBFile {
+    let mut file = LiteDBFile::new();
+    file.set_creation_time(DateTime::from_ymd(2024, 1, 1).unwrap());
+    file.set_random_seed(seed);
+
+    file.ensure_index(
+        "test",
+        "value",
+        BsonExpression::create("$.value").unwrap(),
+        false,
+    )
+    .unwrap();
+    let docs = (0..200)
+        .map(|i| document! {"value" => i % 7})
+        .collect::<Vec<_>>();
+    file.insert("test", docs, BsonAutoId::ObjectId).unwrap();
+    let docs = (0..50)
+        .map(|i| document! {"value" => format!("guid-{i}")})
+        .collect::<Vec<_>>();
+    file.insert("guid", docs, BsonAutoId::Guid).unwrap();
+
+    file
+}
+
+#[test]
+fn same_seed_same_bytes() {
+    let first = build(42);
+    let second = build(42);
+    assert_eq!(first.serialize(), second.serialize());
+
+    let other = build(43);
+    assert_ne!(first.serialize(), other.serialize());
+}
+
+#[test]
+fn seeded_file_is_valid() {
+    let data = build(42).serialize();
+    let issues = LiteDBFile::check_file_integrity(&data);
+    assert!(issues.is_empty(), "{issues:?}");
+
+    let file = LiteDBFile::parse(&data).unwrap();
+    assert_eq!(
+        file.creation_time(),
+        DateTime::from_ymd(2024, 1, 1).unwrap()
+    );
+    assert_eq!(file.get_all("test").count(), 200);
+    assert_eq!(file.get_all("guid").count(), 50);
+}
+
+#[test]
+#[cfg(feature = "encryption")]
+fn same_seed_same_encrypted_bytes() {
+    let mut first = build(42);
+    let mut second = build(42);
+    first.set_password(Some("password"));
+    second.set_password(Some("password"));
+    assert_eq!(first.serialize(), second.serialize());
+}
+
+#[test]
+fn stable_random_values() {
+    // the values for the seed must not change across versions for golden files
+    let mut file = LiteDBFile::new();
+    file.set_random_seed(42);
+    file.insert("test", vec![document! {}], BsonAutoId::ObjectId)
+        .unwrap();
+
+    let id = file.get_all("test").next().unwrap().get("_id").clone();
+    assert_eq!(
+        id,
+        ObjectId::from_bytes([
+            0xa1, 0xb5, 0x88, 0xc6, 0x8c, 0x08, 0x52, 0xf9, 0xb0, 0xd8, 0x9e, 0x5b
+        ])
+        .into()
+    );
+}
@@ -0,0 +1,171 @@
+use std::path::PathBuf;
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("litedb-rs-{name}-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn open_and_save() {
+    let dir = temp_dir("open-and-save");
+    let path = dir.join("vcc.liteDb");
+    std::fs::write(&path, include_bytes!("vcc.liteDb")).unwrap();
+
+    let mut file = LiteDBFile::open(&path).unwrap();
+    file.set_keep_backup(true);
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "value" => "hello"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file.save(&path).unwrap();
+
+    assert_eq!(
+        std::fs::read(dir.join("vcc.liteDb.bak")).unwrap(),
+        include_bytes!("vcc.liteDb")
+    );
+    assert_eq!(
+        std::fs::read_dir(&dir).unwrap().count(),
+        2,
+        "temporary file should not be left"
+    );
+
+    let reopened = LiteDBFile::open(&path).unwrap();
+    assert_eq!(reopened.get_all("test").count(), 1);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn save_merges_log() {
+    let dir = temp_dir("save-merges-log");
+    let path = dir.join("vcc.liteDb");
+    let log_path = dir.join("vcc-log.liteDb");
+    std::fs::write(&path, include_bytes!("vcc.liteDb")).unwrap();
+
+    let mut file = LiteDBFile::open(&path).unwrap();
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+    std::fs::write(&log_path, file.serialize_log().unwrap()).unwrap();
+
+    let mut file = LiteDBFile::open(&path).unwrap();
+    assert_eq!(file.get_all("test").count(), 1);
+
+    let new_path = dir.join("new.liteDb");
+    file.save_as(&new_path).unwrap();
+    assert!(
+        log_path.exists(),
+        "save_as to other file should keep the log"
+    );
+    let copied = LiteDBFile::open(&new_path).unwrap();
+    assert_eq!(copied.get_all("test").count(), 1);
+
+    file.save(&path).unwrap();
+    assert!(!log_path.exists());
+
+    let reopened = LiteDBFile::open(&path).unwrap();
+    assert_eq!(reopened.get_all("test").count(), 1);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn save_checkpoints_log_before_replace() {
+    let dir = temp_dir("save-checkpoints-log");
+    let path = dir.join("vcc.liteDb");
+    let log_path = dir.join("vcc-log.liteDb");
+    std::fs::write(&path, include_bytes!("vcc.liteDb")).unwrap();
+
+    let mut file = LiteDBFile::open(&path).unwrap();
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+    std::fs::write(&log_path, file.serialize_log().unwrap()).unwrap();
+
+    let mut file = LiteDBFile::open(&path).unwrap();
+    file.set_keep_backup(true);
+    file.insert("test", vec![document! {"_id" => 2}], BsonAutoId::ObjectId)
+        .unwrap();
+    file.save(&path).unwrap();
+    assert!(!log_path.exists());
+
+    // the old data file had the log merged before it's replaced
+    let backup = LiteDBFile::parse(&std::fs::read(dir.join("vcc.liteDb.bak")).unwrap()).unwrap();
+    assert_eq!(backup.get_all("test").count(), 1);
+
+    let reopened = LiteDBFile::open(&path).unwrap();
+    assert_eq!(reopened.get_all("test").count(), 2);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn save_keeps_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = temp_dir("save-keeps-permissions");
+    let path = dir.join("vcc.liteDb");
+    std::fs::write(&path, include_bytes!("vcc.liteDb")).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600)).unwrap();
+
+    let mut file = LiteDBFile::open(&path).unwrap();
+    file.save(&path).unwrap();
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "multi_thread")]
+async fn open_and_save_async() {
+    let dir = temp_dir("open-and-save-async");
+    let path = dir.join("vcc.liteDb");
+    std::fs::write(&path, include_bytes!("vcc.liteDb")).unwrap();
+
+    let mut file = LiteDBFile::open_async(&path).await.unwrap();
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+    file.save_async(&path).await.unwrap();
+
+    let reopened = LiteDBFile::open_async(&path).await.unwrap();
+    assert_eq!(reopened.get_all("test").count(), 1);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test(flavor = "current_thread")]
+async fn save_async_on_current_thread() {
+    let dir = temp_dir("save-async-current-thread");
+    let path = dir.join("vcc.liteDb");
+
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+    file.save_async(&path).await.unwrap();
+
+    let reopened = LiteDBFile::open_async(&path).await.unwrap();
+    assert_eq!(reopened.get_all("test").count(), 1);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn save_async_outside_runtime() {
+    let dir = temp_dir("save-async-outside-runtime");
+    let path = dir.join("vcc.liteDb");
+
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    assert!(futures::executor::block_on(file.save_async(&path)).is_err());
+    assert!(!path.exists());
+
+    std::fs::remove_dir_all(&dir).ok();
+}
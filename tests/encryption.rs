@@ -0,0 +1,80 @@
+#![cfg(feature = "encryption")]
+
+use vrc_get_litedb::ErrorKind;
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+#[test]
+fn encrypted_round_trip() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "value" => "secret"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file.set_password(Some("password"));
+    assert!(file.is_encrypted());
+
+    let data = file.serialize();
+    assert!(LiteDBFile::parse(&data).is_err());
+    assert!(LiteDBFile::parse_encrypted(&data, "wrong password").is_err());
+
+    let decrypted = LiteDBFile::parse_encrypted(&data, "password").unwrap();
+    assert!(decrypted.is_encrypted());
+    assert_eq!(
+        decrypted.get_all("test").cloned().collect::<Vec<_>>(),
+        file.get_all("test").cloned().collect::<Vec<_>>()
+    );
+
+    let mut decrypted = decrypted;
+    decrypted.set_password(None);
+    LiteDBFile::parse(&decrypted.serialize()).unwrap();
+}
+
+#[test]
+fn parse_aes_stream_file() {
+    // vcc.liteDb written by LiteDB, encrypted with the layout of AesStream with password "password":
+    // hidden page with marker, salt and password check, then AES-256-ECB with the key
+    // derived by Rfc2898DeriveBytes (PBKDF2-HMAC-SHA1, 1000 iterations).
+    // Encrypted with Python `cryptography` independently of this crate.
+    let encrypted = include_bytes!("vcc.encrypted.liteDb");
+    let plain = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+
+    assert!(LiteDBFile::parse_encrypted(encrypted, "wrong password").is_err());
+    let mut decrypted = LiteDBFile::parse_encrypted(encrypted, "password").unwrap();
+    decrypted.set_password(None);
+    assert!(decrypted.serialize() == plain.serialize());
+}
+
+#[test]
+fn serialize_log_of_encrypted_file() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.set_password(Some("password"));
+    let error = file.serialize_log().unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::EncryptedLog);
+}
+
+#[test]
+fn open_with_unencrypted_log() {
+    let dir =
+        std::env::temp_dir().join(format!("litedb-rs-unencrypted-log-{}", std::process::id()));
+    std::fs::remove_dir_all(&dir).ok();
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("vcc.liteDb");
+    let log_path = dir.join("vcc-log.liteDb");
+    std::fs::write(&path, include_bytes!("vcc.encrypted.liteDb")).unwrap();
+
+    // empty log file is created by LiteDB before the log is used
+    std::fs::write(&log_path, []).unwrap();
+    LiteDBFile::open_encrypted(&path, "password").unwrap();
+
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+    std::fs::write(&log_path, file.serialize_log().unwrap()).unwrap();
+    assert!(LiteDBFile::open_encrypted(&path, "password").is_err());
+    assert!(log_path.exists());
+
+    std::fs::remove_dir_all(&dir).ok();
+}
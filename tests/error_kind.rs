@@ -0,0 +1,116 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::{BsonExpression, ParseErrorKind as ExpressionErrorKind};
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+use vrc_get_litedb::{ErrorKind, ParseErrorKind};
+
+fn test_file() -> LiteDBFile {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "key" => "a"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+    file
+}
+
+#[test]
+fn duplicated_key() {
+    let mut file = test_file();
+    let error = file
+        .insert(
+            "test",
+            vec![document! {"_id" => 2, "key" => "a"}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::DuplicatedIndexKey);
+    assert_eq!(error.index_name(), Some("key"));
+    assert_eq!(error.key(), Some(&bson::Value::from("a")));
+}
+
+#[test]
+fn invalid_field_type() {
+    let mut file = test_file();
+    let error = file
+        .insert(
+            "test",
+            vec![document! {"_id" => bson::Value::MinValue}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap_err();
+
+    assert_eq!(error.kind(), ErrorKind::InvalidFieldType);
+    assert_eq!(error.field(), Some("_id"));
+    assert_eq!(error.key(), Some(&bson::Value::MinValue));
+    assert_eq!(error.index_name(), None);
+}
+
+#[test]
+fn index_errors() {
+    let mut file = test_file();
+
+    let error = file
+        .ensure_index(
+            "test",
+            "key",
+            BsonExpression::create("$.other").unwrap(),
+            true,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::IndexAlreadyExists);
+    assert_eq!(error.index_name(), Some("key"));
+
+    let error = file
+        .ensure_index(
+            "test",
+            "$key",
+            BsonExpression::create("$.key").unwrap(),
+            true,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidIndex);
+    assert_eq!(error.index_name(), Some("$key"));
+
+    let error = file.drop_index("test", "_id").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::DropIdIndex);
+
+    let error = file.rename_collection("test", "$test").unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidCollectionName);
+    assert_eq!(error.collection_name(), Some("$test"));
+}
+
+#[test]
+fn expression_error() {
+    let error = BsonExpression::create("$.a + ").unwrap_err();
+    assert_eq!(error.kind(), ExpressionErrorKind::UnexpectedToken);
+    assert_eq!(error.position(), Some(6));
+
+    let error = BsonExpression::create("UNKNOWN_FUNCTION($.a)").unwrap_err();
+    assert_eq!(error.kind(), ExpressionErrorKind::BadInvocation);
+    assert_eq!(error.position(), Some(0));
+}
+
+#[test]
+fn parse_error() {
+    let error = LiteDBFile::parse(&[0; 8192]).unwrap_err();
+    assert_eq!(error.kind(), ParseErrorKind::InvalidDatabase);
+    assert_eq!(error.page_id(), Some(0));
+    assert!(error.expression_error().is_none());
+
+    // the page id of the second page does not match
+    let mut data = test_file().serialize();
+    data[8192..][..4].copy_from_slice(&100u32.to_le_bytes());
+    let error = LiteDBFile::parse(&data).unwrap_err();
+    assert_eq!(error.kind(), ParseErrorKind::InvalidPage);
+    assert_eq!(error.page_id(), Some(1));
+}
@@ -40,7 +40,7 @@ fn run_test() {
         )
         .unwrap();
 
-    engine.drop_index("unityVersions", "Version");
+    engine.drop_index("unityVersions", "Version").unwrap();
 
     engine.insert("unityVersions", {
         let mut doc = bson::Document::new();
@@ -176,3 +176,39 @@ fn new_file() {
         )
         .expect("index is do");
 }
+
+#[test]
+fn guid_round_trip() {
+    let guid = bson::Guid::from_bytes(*b"0123456789abcdef");
+
+    let mut litedb = LiteDBFile::new();
+    litedb
+        .insert(
+            "test",
+            vec![vrc_get_litedb::document! {"_id" => 1, "value" => guid}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap();
+    litedb
+        .insert(
+            "guids",
+            vec![vrc_get_litedb::document! {}],
+            BsonAutoId::Guid,
+        )
+        .unwrap();
+
+    // Guid is written as binary with subtype 4 like LiteDB does
+    let data = litedb.serialize();
+    let mut expected = vec![0x05]; // binary
+    expected.extend(b"value\0");
+    expected.extend(16i32.to_le_bytes());
+    expected.push(0x04); // UUID subtype
+    expected.extend(guid.as_bytes());
+    assert!(data.windows(expected.len()).any(|x| x == expected));
+
+    let parsed = LiteDBFile::parse(&data).unwrap();
+    let document = parsed.get_all("test").next().unwrap();
+    assert_eq!(document.get("value").as_guid(), Some(guid));
+    let id = parsed.get_all("guids").next().unwrap().get("_id");
+    assert!(id.as_guid().is_some());
+}
@@ -0,0 +1,170 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+const PAGE_SIZE: usize = 8192;
+
+fn changed_pages(old: &[u8], new: &[u8]) -> Vec<usize> {
+    (0..new.len() / PAGE_SIZE)
+        .filter(|&i| {
+            old.get(i * PAGE_SIZE..(i + 1) * PAGE_SIZE) != Some(&new[i * PAGE_SIZE..][..PAGE_SIZE])
+        })
+        .collect()
+}
+
+fn read_u32(page: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(page[offset..][..4].try_into().unwrap())
+}
+
+/// Checks all pages in free lists are linked correctly
+fn check_free_lists(data: &[u8]) {
+    let pages = data.chunks(PAGE_SIZE).collect::<Vec<_>>();
+    let page_type = |id: u32| pages[id as usize][4];
+    let prev = |id: u32| read_u32(pages[id as usize], 5);
+    let next = |id: u32| read_u32(pages[id as usize], 9);
+    let list_slot = |id: u32| pages[id as usize][13];
+    let col_id = |id: u32| read_u32(pages[id as usize], 19);
+
+    let mut listed = vec![false; pages.len()];
+
+    // empty pages
+    let mut current = read_u32(pages[0], 60);
+    while current != u32::MAX {
+        assert_eq!(page_type(current), 0);
+        assert!(!std::mem::replace(&mut listed[current as usize], true));
+        current = next(current);
+    }
+
+    for (col, page) in pages.iter().enumerate() {
+        if page[4] != 2 {
+            continue;
+        }
+        for slot in 0..5 {
+            let mut prev_id = u32::MAX;
+            let mut current = read_u32(page, 32 + slot * 4);
+            while current != u32::MAX {
+                assert_eq!(page_type(current), 4);
+                assert_eq!(list_slot(current) as usize, slot);
+                assert_eq!(col_id(current) as usize, col);
+                assert_eq!(prev(current), prev_id);
+                assert!(!std::mem::replace(&mut listed[current as usize], true));
+                prev_id = current;
+                current = next(current);
+            }
+        }
+    }
+
+    // all data pages must be on the list
+    for (id, page) in pages.iter().enumerate() {
+        if page[4] == 4 {
+            assert!(listed[id], "data page {id} is not on free list");
+        }
+    }
+}
+
+fn assert_same(file: &LiteDBFile, data: &[u8]) {
+    let parsed = LiteDBFile::parse(data).unwrap();
+    assert_eq!(parsed.get_collection_names(), file.get_collection_names());
+    for collection in file.get_collection_names() {
+        assert_eq!(
+            parsed.get_all(&collection).collect::<Vec<_>>(),
+            file.get_all(&collection).collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[test]
+fn unchanged_file_is_not_modified() {
+    let data = include_bytes!("vcc.liteDb");
+    let file = LiteDBFile::parse(data).unwrap();
+    assert_eq!(file.serialize(), data);
+}
+
+#[test]
+fn update_rewrites_affected_pages_only() {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        (0..2000)
+            .map(|id| document! {"_id" => id, "value" => format!("value {id}")})
+            .collect(),
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    let data = file.serialize();
+    let mut file = LiteDBFile::parse(&data).unwrap();
+
+    file.update(
+        "test",
+        vec![document! {"_id" => 1000, "value" => "updated"}],
+    )
+    .unwrap();
+    file.delete("test", &[bson::Value::Int32(500)]);
+
+    let written = file.serialize();
+    assert_eq!(written.len(), data.len());
+    assert!(changed_pages(&data, &written).len() < data.len() / PAGE_SIZE / 2);
+    assert_same(&file, &written);
+    check_free_lists(&written);
+}
+
+#[test]
+fn repeated_changes() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        false,
+    )
+    .unwrap();
+
+    let mut random = 12345u32;
+    let mut next_random = move |max: u32| {
+        random = random.wrapping_mul(1103515245).wrapping_add(12345);
+        (random >> 8) % max
+    };
+
+    for round in 0..30 {
+        for _ in 0..20 {
+            let id = next_random(100) as i32;
+            // some documents are larger than one page
+            let value = "x".repeat(next_random(20000) as usize);
+            match next_random(3) {
+                0 => {
+                    file.upsert(
+                        "test",
+                        vec![document! {"_id" => id, "key" => id % 7, "value" => value}],
+                        BsonAutoId::ObjectId,
+                    )
+                    .unwrap();
+                }
+                _ => {
+                    file.delete("test", &[bson::Value::Int32(id)]);
+                }
+            }
+        }
+
+        match round {
+            10 => {
+                file.drop_index("test", "key").unwrap();
+            }
+            15 => {
+                file.drop_collection("projects");
+            }
+            _ => {}
+        }
+
+        let written = file.serialize();
+        check_free_lists(&written);
+        assert_same(&file, &written);
+        assert!(file.check_integrity().is_empty());
+        assert!(LiteDBFile::check_file_integrity(&written).is_empty());
+
+        // use written file as next base
+        if round % 2 == 0 {
+            file = LiteDBFile::parse(&written).unwrap();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::LiteDBFile;
+
+#[test]
+fn list_indexes() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.ensure_index(
+        "projects",
+        "unique_path",
+        BsonExpression::create("$.Path").unwrap(),
+        true,
+    )
+    .unwrap();
+
+    let indexes = file.indexes("PROJECTS");
+    let names = indexes.iter().map(|x| x.name.as_str()).collect::<Vec<_>>();
+    assert_eq!(names.first(), Some(&"_id"));
+    assert!(names.contains(&"Path"));
+
+    let pk = &indexes[0];
+    assert_eq!(pk.collection, "projects");
+    assert_eq!(pk.slot, 0);
+    assert_eq!(pk.index_type, 0);
+    assert_eq!(pk.expression, "$._id");
+    assert!(pk.unique);
+    assert!(pk.max_level >= 1);
+
+    let added = indexes.iter().find(|x| x.name == "unique_path").unwrap();
+    assert_eq!(added.expression, "$.Path");
+    assert!(added.unique);
+    assert_eq!(added.slot as usize, indexes.len() - 1);
+
+    // indexes can be re-created from the listed definitions
+    for index in &indexes {
+        let created = file
+            .ensure_index(
+                "projects",
+                &index.name,
+                BsonExpression::create(&index.expression).unwrap(),
+                index.unique,
+            )
+            .unwrap();
+        assert!(!created);
+    }
+
+    // survives round trip
+    let file = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(file.indexes("projects"), indexes);
+}
+
+#[test]
+fn missing_collection() {
+    let file = LiteDBFile::new();
+    assert!(file.indexes("missing").is_empty());
+}
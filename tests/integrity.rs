@@ -0,0 +1,147 @@
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, IntegrityIssue, LiteDBFile};
+
+const PAGE_SIZE: usize = 8192;
+const PAGE_TYPE_INDEX: u8 = 3;
+const P_FREE_EMPTY_PAGE_ID: usize = 60;
+const P_NEXT_PAGE_ID: usize = 9;
+
+fn unique_index_file() -> Vec<u8> {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        vec![
+            document! {"_id" => 1, "key" => "key-1"},
+            document! {"_id" => 2, "key" => "key-2"},
+            document! {"_id" => 3, "key" => "key-3"},
+        ],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+    file.serialize()
+}
+
+/// Replaces the index key in the index pages
+fn replace_index_key(data: &mut [u8], from: &[u8], to: &[u8]) {
+    for page in data.chunks_mut(PAGE_SIZE) {
+        if page[4] != PAGE_TYPE_INDEX {
+            continue;
+        }
+        if let Some(position) = page.windows(from.len()).position(|x| x == from) {
+            page[position..][..to.len()].copy_from_slice(to);
+            return;
+        }
+    }
+    panic!("index key not found");
+}
+
+#[test]
+fn valid_database() {
+    let data = include_bytes!("vcc.liteDb");
+    assert!(LiteDBFile::check_file_integrity(data).is_empty());
+
+    let mut file = LiteDBFile::parse(data).unwrap();
+    assert!(file.check_integrity().is_empty());
+
+    file.drop_collection("projects");
+    file.insert(
+        "unityVersions",
+        vec![document! {"Path" => "/path/to/unity", "Version" => "2022.3.22f1"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    assert!(file.check_integrity().is_empty());
+    assert!(LiteDBFile::check_file_integrity(&file.serialize()).is_empty());
+
+    assert!(LiteDBFile::check_file_integrity(&unique_index_file()).is_empty());
+}
+
+#[test]
+fn invalid_file() {
+    let issues = LiteDBFile::check_file_integrity(&[0xFF; PAGE_SIZE]);
+    assert!(matches!(issues[..], [IntegrityIssue::InvalidFile(_)]));
+}
+
+#[test]
+fn empty_file() {
+    assert!(LiteDBFile::check_file_integrity(&[]).is_empty());
+}
+
+#[test]
+fn duplicated_key() {
+    let mut data = unique_index_file();
+    replace_index_key(&mut data, b"key-2", b"key-1");
+
+    let issues = LiteDBFile::check_file_integrity(&data);
+    assert!(
+        issues.iter().any(|x| matches!(
+            x,
+            IntegrityIssue::DuplicateKey { collection, index, .. } if collection == "test" && index == "key"
+        )),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn unordered_index() {
+    let mut data = unique_index_file();
+    replace_index_key(&mut data, b"key-2", b"key-4");
+
+    let issues = LiteDBFile::check_file_integrity(&data);
+    assert!(
+        issues.iter().any(|x| matches!(
+            x,
+            IntegrityIssue::UnorderedIndex { collection, index, level: 0 } if collection == "test" && index == "key"
+        )),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn cyclic_free_list() {
+    let mut data = unique_index_file();
+    let page = data.len() / PAGE_SIZE;
+
+    // add an empty page pointing to itself
+    data.resize(data.len() + PAGE_SIZE, 0);
+    let empty_page = &mut data[page * PAGE_SIZE..];
+    empty_page[..4].copy_from_slice(&(page as u32).to_le_bytes());
+    empty_page[P_NEXT_PAGE_ID..][..4].copy_from_slice(&(page as u32).to_le_bytes());
+    data[P_FREE_EMPTY_PAGE_ID..][..4].copy_from_slice(&(page as u32).to_le_bytes());
+
+    let issues = LiteDBFile::check_file_integrity(&data);
+    assert!(
+        matches!(issues[..], [IntegrityIssue::BrokenFreeList { first_page }] if first_page == page as u32),
+        "{issues:?}"
+    );
+}
+
+#[test]
+fn wrong_page_in_free_list() {
+    let mut data = unique_index_file();
+    let index_page = data
+        .chunks(PAGE_SIZE)
+        .position(|page| page[4] == PAGE_TYPE_INDEX)
+        .unwrap();
+
+    // index page in the empty page list
+    data[P_FREE_EMPTY_PAGE_ID..][..4].copy_from_slice(&(index_page as u32).to_le_bytes());
+
+    let issues = LiteDBFile::check_file_integrity(&data);
+    assert!(
+        issues.iter().any(|x| matches!(
+            x,
+            IntegrityIssue::InvalidFreeListPage { first_page, page_id }
+                if *first_page == index_page as u32 && *page_id == index_page as u32
+        )),
+        "{issues:?}"
+    );
+}
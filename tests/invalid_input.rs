@@ -0,0 +1,132 @@
+use vrc_get_litedb::ErrorKind;
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile, RenameCollectionResult};
+
+fn test_file() -> LiteDBFile {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "key" => "a"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file
+}
+
+#[test]
+fn invalid_index() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    let key = || BsonExpression::create("$.key").unwrap();
+    assert!(file.ensure_index("test", "$key", key(), false).is_err());
+    assert!(file.ensure_index("test", "key-1", key(), false).is_err());
+    assert!(file.ensure_index("test", "", key(), false).is_err());
+
+    // not reading any fields
+    let constant = BsonExpression::create("1").unwrap();
+    assert!(file.ensure_index("test", "key", constant, false).is_err());
+
+    // multiple values with unique index
+    let multi = BsonExpression::create("$.keys[*].a").unwrap();
+    assert!(file.ensure_index("test", "key", multi, true).is_err());
+
+    // invalid collection name
+    assert!(file.ensure_index("$test", "key", key(), false).is_err());
+
+    assert_eq!(file.serialize(), original);
+}
+
+#[test]
+fn drop_id_index() {
+    let mut file = test_file();
+    assert!(file.drop_index("test", "_id").is_err());
+    assert!(!file.drop_index("test", "missing").unwrap());
+    assert_eq!(file.get_all("test").count(), 1);
+}
+
+#[test]
+fn invalid_id() {
+    let mut file = test_file();
+
+    for id in [
+        bson::Value::Null,
+        bson::Value::MinValue,
+        bson::Value::MaxValue,
+    ] {
+        let result = file.insert(
+            "test",
+            vec![document! {"_id" => id, "key" => "b"}],
+            BsonAutoId::ObjectId,
+        );
+        assert!(result.is_err());
+    }
+
+    assert_eq!(file.get_all("test").count(), 1);
+    assert!(file.check_integrity().is_empty());
+}
+
+#[test]
+fn invalid_collection_name() {
+    let mut file = test_file();
+    let doc = || vec![document! {"key" => "b"}];
+
+    for name in ["", "$test", "test collection", "test-1", "1test"] {
+        assert!(
+            file.insert(name, doc(), BsonAutoId::ObjectId).is_err(),
+            "{name}"
+        );
+        assert!(
+            file.upsert(name, doc(), BsonAutoId::ObjectId).is_err(),
+            "{name}"
+        );
+        assert!(file.rename_collection("test", name).is_err(), "{name}");
+    }
+    assert_eq!(file.get_collection_names(), vec!["test"]);
+
+    // the header page has no space for the name
+    let long_name = "a".repeat(8000);
+    assert!(
+        file.insert(&long_name, doc(), BsonAutoId::ObjectId)
+            .is_err()
+    );
+    assert!(file.rename_collection("test", &long_name).is_err());
+
+    assert!(matches!(
+        file.rename_collection("test", "renamed").unwrap(),
+        RenameCollectionResult::Renamed
+    ));
+    assert_eq!(file.get_collection_names(), vec!["renamed"]);
+}
+
+#[test]
+fn invalid_document_key() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    let mut document = bson::Document::new();
+    document.insert("key\0", 1);
+    let error = file
+        .insert("test", vec![document], BsonAutoId::ObjectId)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidDocumentKey);
+    assert_eq!(error.field(), Some("key\0"));
+
+    let mut nested = bson::Document::new();
+    nested.entry("nested\0").or_insert(1);
+    let document = document! {"_id" => 1, "value" => bson::Array::from(vec![nested.into()])};
+    let error = file
+        .upsert("test", vec![document], BsonAutoId::ObjectId)
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidDocumentKey);
+
+    assert_eq!(file.serialize(), original);
+}
+
+#[test]
+fn missing_index() {
+    let file = test_file();
+    assert_eq!(file.get_by_index("test", "missing", &"a".into()).count(), 0);
+}
@@ -0,0 +1,70 @@
+use std::io::Cursor;
+use vrc_get_litedb::ParseErrorKind;
+use vrc_get_litedb::bson;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{LazyLiteDBFile, LiteDBFile, Order};
+
+#[test]
+fn same_as_parsed() {
+    let mut parsed = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    parsed
+        .ensure_index(
+            "unityVersions",
+            "version",
+            BsonExpression::create("Version").unwrap(),
+            false,
+        )
+        .unwrap();
+    let data = parsed.serialize();
+
+    // small cache to test eviction
+    let lazy = LazyLiteDBFile::with_cache_capacity(Cursor::new(&data), 2).unwrap();
+
+    assert_eq!(lazy.get_collection_names(), parsed.get_collection_names());
+
+    for collection in parsed.get_collection_names() {
+        assert_eq!(
+            lazy.get_all(&collection)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            parsed.get_all(&collection).cloned().collect::<Vec<_>>(),
+        );
+    }
+
+    let version: bson::Value = "2022.3.22f1".to_string().into();
+    assert_eq!(
+        lazy.get_by_index("unityVersions", "version", &version)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap(),
+        parsed
+            .get_by_index("unityVersions", "version", &version)
+            .cloned()
+            .collect::<Vec<_>>(),
+    );
+
+    let min: bson::Value = "2019".to_string().into();
+    let max: bson::Value = "2023".to_string().into();
+    for order in [Order::Ascending, Order::Descending] {
+        assert_eq!(
+            lazy.get_range_indexed("unityVersions", "version", &min, &max, order)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap(),
+            parsed
+                .get_range_indexed("unityVersions", "version", &min, &max, order)
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+    }
+}
+
+#[test]
+fn encrypted_file() {
+    let data = include_bytes!("vcc.encrypted.liteDb");
+    let error = LazyLiteDBFile::new(Cursor::new(data)).err().unwrap();
+    let error = error
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<vrc_get_litedb::ParseError>()
+        .unwrap();
+    assert_eq!(error.kind(), ParseErrorKind::Encrypted);
+}
@@ -0,0 +1,166 @@
+use vrc_get_litedb::ParseErrorKind;
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+const PAGE_SIZE: usize = 8192;
+
+fn versions(file: &LiteDBFile) -> Vec<bson::Document> {
+    file.get_all("unityVersions").cloned().collect()
+}
+
+#[test]
+fn log_round_trip() {
+    let data = include_bytes!("vcc.liteDb");
+    let mut file = LiteDBFile::parse(data).unwrap();
+
+    assert!(file.serialize_log().unwrap().is_empty());
+
+    file.insert(
+        "unityVersions",
+        vec![document! {"Path" => "/path/to/unity", "Version" => "2022.3.22f1"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    let log = file.serialize_log().unwrap();
+    assert!(!log.is_empty());
+    assert_eq!(log.len() % PAGE_SIZE, 0);
+
+    let applied = LiteDBFile::parse_with_log(data, &log).unwrap();
+    assert_eq!(versions(&applied), versions(&file));
+
+    // second transaction will be appended to the log
+    file.delete(
+        "unityVersions",
+        &[versions(&file).last().unwrap().get("_id").clone()],
+    );
+
+    let mut log = log;
+    log.extend_from_slice(&file.serialize_log().unwrap());
+
+    let applied = LiteDBFile::parse_with_log(data, &log).unwrap();
+    assert_eq!(versions(&applied), versions(&file));
+
+    // nothing is changed since the last serialize_log
+    assert!(file.serialize_log().unwrap().is_empty());
+}
+
+#[test]
+fn unconfirmed_transaction_is_ignored() {
+    let data = include_bytes!("vcc.liteDb");
+    let mut file = LiteDBFile::parse(data).unwrap();
+
+    file.drop_collection("unityVersions");
+
+    let log = file.serialize_log().unwrap();
+
+    // the last page confirms the transaction
+    let applied = LiteDBFile::parse_with_log(data, &log[..log.len() - PAGE_SIZE]).unwrap();
+    assert_eq!(
+        versions(&applied),
+        versions(&LiteDBFile::parse(data).unwrap())
+    );
+}
+
+const P_PAGE_ID: usize = 0;
+const P_TRANSACTION_ID: usize = 14;
+const P_IS_CONFIRMED: usize = 18;
+
+fn page(data: &[u8], page_id: usize) -> &[u8] {
+    &data[page_id * PAGE_SIZE..][..PAGE_SIZE]
+}
+
+/// Appends the page to the log with the transaction header like `WalService` of LiteDB does
+fn log_page(log: &mut Vec<u8>, page: &[u8], transaction_id: u32, confirmed: bool) {
+    let start = log.len();
+    log.extend_from_slice(page);
+    log[start + P_TRANSACTION_ID..][..4].copy_from_slice(&transaction_id.to_le_bytes());
+    log[start + P_IS_CONFIRMED] = confirmed as u8;
+}
+
+/// The page with the page id but other bytes broken
+fn broken_page(page_id: usize) -> Vec<u8> {
+    let mut page = vec![0xCD; PAGE_SIZE];
+    page[P_PAGE_ID..][..4].copy_from_slice(&(page_id as u32).to_le_bytes());
+    page
+}
+
+#[test]
+fn apply_log_of_litedb_layout() {
+    // the log file layout written by LiteDB, built from the pages of vcc.liteDb written by LiteDB.
+    let expected = include_bytes!("vcc.liteDb");
+    let page_count = expected.len() / PAGE_SIZE;
+
+    // the data file before checkpoint: last two pages only exist in the log, and page 2 is outdated
+    let mut data = expected[..(page_count - 2) * PAGE_SIZE].to_vec();
+    data[2 * PAGE_SIZE..][..PAGE_SIZE].copy_from_slice(&broken_page(2));
+
+    let mut log = Vec::new();
+    // transaction 5: pages beyond the end of data file, and broken page 2 fixed later
+    log_page(&mut log, page(expected, page_count - 2), 5, false);
+    log_page(&mut log, &broken_page(2), 5, false);
+    // transaction 7 never confirmed, interleaved with others
+    log_page(&mut log, &broken_page(5), 7, false);
+    log_page(&mut log, page(expected, page_count - 1), 5, false);
+    log_page(&mut log, page(expected, 0), 5, true);
+    // transaction 6: later transaction wins
+    log_page(&mut log, page(expected, 2), 6, false);
+    log_page(&mut log, page(expected, 0), 6, true);
+    // unconfirmed page of transaction 7
+    log_page(&mut log, &broken_page(0), 7, false);
+
+    let applied = LiteDBFile::parse_with_log(&data, &log).unwrap();
+    let expected = LiteDBFile::parse(expected).unwrap();
+    assert_eq!(applied.serialize(), expected.serialize());
+    assert!(applied.check_integrity().is_empty());
+
+    // without the confirmation of transaction 6, broken page 2 of transaction 5 is used
+    let transaction_5 = &log[..5 * PAGE_SIZE];
+    assert!(LiteDBFile::parse_with_log(&data, transaction_5).is_err());
+}
+
+#[test]
+fn gap_after_data_file_is_empty_page() {
+    let data = include_bytes!("vcc.liteDb");
+    let page_count = data.len() / PAGE_SIZE;
+
+    // new empty page added after a page which is never written
+    let mut empty = vec![0; PAGE_SIZE];
+    empty[P_PAGE_ID..][..4].copy_from_slice(&((page_count + 1) as u32).to_le_bytes());
+    empty[P_TRANSACTION_ID..][..4].copy_from_slice(&u32::MAX.to_le_bytes());
+    let mut log = Vec::new();
+    log_page(&mut log, &empty, 1, false);
+    log_page(&mut log, page(data, 0), 1, true);
+
+    let applied = LiteDBFile::parse_with_log(data, &log).unwrap();
+    assert_eq!(
+        versions(&applied),
+        versions(&LiteDBFile::parse(data).unwrap())
+    );
+}
+
+#[test]
+fn zero_page_in_data_file_is_kept() {
+    let mut data = include_bytes!("vcc.liteDb").to_vec();
+    data.resize(data.len() + PAGE_SIZE, 0);
+
+    // the zero page in the data file is not a page written by LiteDB
+    assert!(LiteDBFile::parse(&data).is_err());
+    assert!(LiteDBFile::parse_with_log(&data, &[]).is_err());
+}
+
+#[test]
+fn corrupt_log_page_id() {
+    let data = include_bytes!("vcc.liteDb");
+
+    // page id far beyond the end of the data file must not be allocated
+    let page_id = u32::MAX - 1;
+    let mut log = Vec::new();
+    log_page(&mut log, &broken_page(page_id as usize), 1, false);
+    log_page(&mut log, page(data, 0), 1, true);
+
+    let error = LiteDBFile::parse_with_log(data, &log).unwrap_err();
+    assert_eq!(error.kind(), ParseErrorKind::InvalidPage);
+    assert_eq!(error.page_id(), Some(page_id));
+}
@@ -0,0 +1,73 @@
+use vrc_get_litedb::ParseErrorKind;
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LazyLiteDBFile, LiteDBFile};
+
+const PAGE_SIZE: usize = 8192;
+const PAGE_TYPE_EMPTY: u8 = 0;
+const PAGE_TYPE_DATA: u8 = 4;
+
+fn test_file() -> Vec<u8> {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "marker" => "value"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file.serialize()
+}
+
+fn data_page(data: &[u8]) -> usize {
+    data.chunks(PAGE_SIZE)
+        .position(|page| page[4] == PAGE_TYPE_DATA)
+        .unwrap()
+}
+
+#[test]
+fn bad_reference() {
+    let mut data = test_file();
+    let page = data_page(&data);
+    data[page * PAGE_SIZE + 4] = PAGE_TYPE_EMPTY;
+
+    let error = LiteDBFile::parse(&data).unwrap_err();
+    assert_eq!(error.kind(), ParseErrorKind::BadReference);
+    assert_eq!(error.collection(), Some("test"));
+    assert_eq!(error.index(), Some("_id"));
+    assert_eq!(error.address(), Some((page as u32, 0)));
+    assert_eq!(error.page_id(), Some(page as u32));
+
+    let message = error.to_string();
+    assert!(message.contains("collection `test`"), "{message}");
+    assert!(message.contains("index `_id`"), "{message}");
+    assert!(message.contains(&format!("address {page}:0")), "{message}");
+}
+
+#[test]
+fn invalid_bson() {
+    let mut data = test_file();
+    let page = data_page(&data);
+
+    // replace the type tag of "marker" field with an invalid one
+    let position = data.windows(7).position(|x| x == b"marker\0").unwrap();
+    data[position - 1] = 0x55;
+
+    let error = LiteDBFile::parse(&data).unwrap_err();
+    assert_eq!(error.kind(), ParseErrorKind::InvalidBson);
+    assert_eq!(error.collection(), Some("test"));
+    assert_eq!(error.address(), Some((page as u32, 0)));
+    // 4 bytes length, and `_id` field with tag, key, and int32 value
+    let tag_offset = 4 + 1 + 4 + 4;
+    assert_eq!(error.offset(), Some(tag_offset + 1));
+
+    // lazy file reports the same location on reading the document
+    let file = LazyLiteDBFile::new(std::io::Cursor::new(data)).unwrap();
+    let error = file.get_all("test").next().unwrap().unwrap_err();
+    let error = error
+        .get_ref()
+        .unwrap()
+        .downcast_ref::<vrc_get_litedb::ParseError>()
+        .unwrap();
+    assert_eq!(error.kind(), ParseErrorKind::InvalidBson);
+    assert_eq!(error.address(), Some((page as u32, 0)));
+    assert_eq!(error.offset(), Some(tag_offset + 1));
+}
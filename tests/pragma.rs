@@ -0,0 +1,68 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::file_io::{Collation, CompareOptions, LiteDBFile};
+
+fn documents(file: &LiteDBFile, collection: &str) -> Vec<bson::Document> {
+    file.get_all(collection).cloned().collect()
+}
+
+#[test]
+fn pragmas_round_trip() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+
+    file.set_user_version(3);
+    file.set_utc_date(true);
+    file.set_timeout_seconds(30).unwrap();
+    file.set_checkpoint(0).unwrap();
+    file.set_limit_size(1024 * 1024).unwrap();
+
+    let file = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(file.user_version(), 3);
+    assert!(file.utc_date());
+    assert_eq!(file.timeout_seconds(), 30);
+    assert_eq!(file.checkpoint(), 0);
+    assert_eq!(file.limit_size(), 1024 * 1024);
+}
+
+#[test]
+fn invalid_pragma_values() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let timeout = file.timeout_seconds();
+    let checkpoint = file.checkpoint();
+    let limit_size = file.limit_size();
+
+    assert!(file.set_timeout_seconds(0).is_err());
+    assert!(file.set_checkpoint(-1).is_err());
+    // at least 4 pages
+    assert!(file.set_limit_size(8192 * 3).is_err());
+    // smaller than the current file
+    assert!(file.set_limit_size(8192 * 4).is_err());
+
+    assert_eq!(file.timeout_seconds(), timeout);
+    assert_eq!(file.checkpoint(), checkpoint);
+    assert_eq!(file.limit_size(), limit_size);
+}
+
+#[test]
+fn change_collation() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let projects = documents(&file, "projects");
+    let versions = documents(&file, "unityVersions");
+
+    let collation = Collation::new(1033, CompareOptions::IGNORE_CASE);
+    file.set_collation(collation).unwrap();
+
+    assert_eq!(file.collation(), collation);
+    assert!(file.check_integrity().is_empty());
+
+    let file = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(file.collation(), collation);
+    assert_eq!(documents(&file, "projects"), projects);
+    assert_eq!(documents(&file, "unityVersions"), versions);
+
+    let project = &projects[0];
+    assert_eq!(
+        file.get_by_index("projects", "Path", project.get("Path"))
+            .collect::<Vec<_>>(),
+        vec![project]
+    );
+}
@@ -0,0 +1,107 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile, RebuildOptions};
+
+fn documents(file: &LiteDBFile, collection: &str) -> Vec<bson::Document> {
+    file.get_all(collection).cloned().collect()
+}
+
+#[test]
+fn rebuild_compacts_file() {
+    let mut file = LiteDBFile::new();
+    let docs = (0..1000)
+        .map(|i| document! {"_id" => i, "value" => format!("value-{i:0100}")})
+        .collect::<Vec<_>>();
+    file.insert("test", docs, BsonAutoId::ObjectId).unwrap();
+    file.ensure_index(
+        "test",
+        "value",
+        BsonExpression::create("$.value").unwrap(),
+        true,
+    )
+    .unwrap();
+    let mut file = LiteDBFile::parse(&file.serialize()).unwrap();
+
+    let ids = (0..1000).filter(|i| i % 10 != 0).map(bson::Value::from);
+    file.delete("test", &ids.collect::<Vec<_>>());
+    let remaining = documents(&file, "test");
+
+    let report = file.rebuild(RebuildOptions::default()).unwrap();
+    assert!(report.size_after < report.size_before, "{report:?}");
+    assert!(report.dropped_indexes.is_empty());
+    assert_eq!(documents(&file, "test"), remaining);
+    assert!(file.check_integrity().is_empty());
+
+    let data = file.serialize();
+    assert_eq!(data.len(), report.size_after);
+    assert!(LiteDBFile::check_file_integrity(&data).is_empty());
+
+    let file = LiteDBFile::parse(&data).unwrap();
+    assert_eq!(documents(&file, "test"), remaining);
+    let found = file
+        .get_by_index("test", "value", &remaining[1].get("value").clone())
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![&remaining[1]]);
+}
+
+#[test]
+#[cfg(feature = "icu-collation")]
+fn rebuild_with_options() {
+    use vrc_get_litedb::file_io::{Collation, CompareOptions};
+
+    let mut file = LiteDBFile::new();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+    file.insert(
+        "test",
+        vec![
+            document! {"_id" => 1, "key" => "a"},
+            document! {"_id" => 2, "key" => "A"},
+        ],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    let collation = Collation::new(127, CompareOptions::IGNORE_CASE);
+    let report = file
+        .rebuild(RebuildOptions {
+            collation: Some(collation),
+            user_version: Some(5),
+            ..Default::default()
+        })
+        .unwrap();
+
+    // "a" and "A" are duplicated under the new collation
+    assert_eq!(
+        report.dropped_indexes,
+        vec![("test".to_string(), "key".to_string())]
+    );
+    assert_eq!(file.collation(), collation);
+    assert_eq!(file.user_version(), 5);
+    assert_eq!(file.get_all("test").count(), 2);
+    assert!(file.check_integrity().is_empty());
+
+    let file = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(file.collation(), collation);
+    assert_eq!(file.user_version(), 5);
+}
+
+#[test]
+fn invalid_options() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let original = file.serialize();
+
+    let result = file.rebuild(RebuildOptions {
+        user_version: Some(5),
+        timeout_seconds: Some(0),
+        ..Default::default()
+    });
+    assert!(result.is_err());
+    assert_eq!(file.serialize(), original);
+}
@@ -0,0 +1,168 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::LiteDBFile;
+
+const PAGE_SIZE: usize = 8192;
+const PAGE_TYPE_INDEX: u8 = 3;
+const PAGE_TYPE_DATA: u8 = 4;
+
+fn test_file() -> Vec<u8> {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.ensure_index(
+        "projects",
+        "path",
+        BsonExpression::create("$.Path").unwrap(),
+        true,
+    )
+    .unwrap();
+    file.serialize()
+}
+
+fn pages_of_type(data: &[u8], page_type: u8) -> Vec<usize> {
+    data.chunks(PAGE_SIZE)
+        .enumerate()
+        .filter(|(_, page)| page[4] == page_type)
+        .map(|(index, _)| index)
+        .collect()
+}
+
+fn break_page(data: &mut [u8], page: usize) {
+    // the page id does not match the position
+    data[page * PAGE_SIZE..][..4].copy_from_slice(&u32::MAX.to_le_bytes());
+}
+
+fn documents(file: &LiteDBFile, collection: &str) -> Vec<bson::Document> {
+    let mut documents = file.get_all(collection).cloned().collect::<Vec<_>>();
+    documents.sort_by_key(|doc| format!("{:?}", doc.get("_id")));
+    documents
+}
+
+#[test]
+fn clean_file() {
+    let data = test_file();
+    let parsed = LiteDBFile::parse(&data).unwrap();
+
+    let (recovered, report) = LiteDBFile::parse_recovering(&data).unwrap();
+
+    assert!(report.is_clean());
+    assert_eq!(
+        recovered.get_collection_names(),
+        parsed.get_collection_names()
+    );
+    for collection in report.collections {
+        assert_eq!(
+            collection.recovered_documents,
+            parsed.get_all(&collection.name).count()
+        );
+    }
+}
+
+#[test]
+fn damaged_index_pages() {
+    let original = test_file();
+    let parsed = LiteDBFile::parse(&original).unwrap();
+
+    let mut data = original.clone();
+    let index_pages = pages_of_type(&data, PAGE_TYPE_INDEX);
+    for &page in &index_pages {
+        break_page(&mut data, page);
+    }
+
+    assert!(LiteDBFile::parse(&data).is_err());
+
+    let (recovered, report) = LiteDBFile::parse_recovering(&data).unwrap();
+
+    assert_eq!(
+        report.damaged_pages,
+        index_pages.iter().map(|&x| x as u32).collect::<Vec<_>>()
+    );
+    assert!(report.orphan_pages.is_empty());
+    assert!(report.collections.iter().all(|x| x.is_clean()));
+
+    // all documents are recovered from data pages
+    for name in parsed.get_collection_names() {
+        assert_eq!(documents(&recovered, &name), documents(&parsed, &name));
+    }
+
+    // secondary index is rebuilt
+    let project = parsed.get_all("projects").next().unwrap();
+    let found = recovered
+        .get_by_index("projects", "path", project.get("Path"))
+        .collect::<Vec<_>>();
+    assert_eq!(found, vec![project]);
+
+    // recovered database can be written and parsed again
+    let reparsed = LiteDBFile::parse(&recovered.serialize()).unwrap();
+    for name in parsed.get_collection_names() {
+        assert_eq!(documents(&reparsed, &name), documents(&parsed, &name));
+    }
+}
+
+#[test]
+fn damaged_data_page() {
+    let original = test_file();
+    let parsed = LiteDBFile::parse(&original).unwrap();
+
+    let mut data = original.clone();
+    let data_pages = pages_of_type(&data, PAGE_TYPE_DATA);
+    let damaged = data_pages[0];
+    break_page(&mut data, damaged);
+
+    assert!(LiteDBFile::parse(&data).is_err());
+
+    let (recovered, report) = LiteDBFile::parse_recovering(&data).unwrap();
+
+    assert_eq!(report.damaged_pages, vec![damaged as u32]);
+    assert!(!report.is_clean());
+
+    let mut recovered_count = 0;
+    let mut original_count = 0;
+    for name in parsed.get_collection_names() {
+        let original = documents(&parsed, &name);
+        let recovered = documents(&recovered, &name);
+        // recovered documents are not modified
+        for document in &recovered {
+            assert!(original.contains(document));
+        }
+        recovered_count += recovered.len();
+        original_count += original.len();
+    }
+
+    // some documents are lost with the page, but documents in other pages are recovered
+    assert!(recovered_count < original_count);
+    assert!(recovered_count > 0);
+    assert_eq!(
+        report
+            .collections
+            .iter()
+            .map(|x| x.recovered_documents)
+            .sum::<usize>(),
+        recovered_count
+    );
+}
+
+#[test]
+fn damaged_collection_page() {
+    let original = test_file();
+    let parsed = LiteDBFile::parse(&original).unwrap();
+
+    let mut data = original.clone();
+    // the first collection page is next to the header page
+    break_page(&mut data, 1);
+
+    let (recovered, report) = LiteDBFile::parse_recovering(&data).unwrap();
+
+    assert_eq!(report.damaged_pages, vec![1]);
+    let lost = report
+        .collections
+        .iter()
+        .filter(|x| x.lost_index_definitions)
+        .collect::<Vec<_>>();
+    assert_eq!(lost.len(), 1);
+
+    // documents are still recovered
+    assert_eq!(
+        documents(&recovered, &lost[0].name),
+        documents(&parsed, &lost[0].name)
+    );
+}
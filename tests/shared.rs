@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile, SharedLiteDB};
+
+fn shared() -> SharedLiteDB {
+    SharedLiteDB::new(LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap())
+}
+
+#[test]
+fn snapshot_is_not_affected_by_write() {
+    let db = shared();
+
+    let snapshot = db.read();
+    let mut projects = snapshot.get_all("projects");
+    let first = projects.next().unwrap();
+    let count = snapshot.get_all("projects").count();
+
+    db.write(|file| {
+        file.drop_collection("projects");
+        file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+            .unwrap();
+    });
+
+    // the iterator is still valid
+    assert_eq!(projects.count(), count - 1);
+    assert_eq!(snapshot.get_all("projects").next(), Some(first));
+    assert_eq!(snapshot.get_all("test").count(), 0);
+
+    let latest = db.read();
+    assert_eq!(latest.get_all("projects").count(), 0);
+    assert_eq!(latest.get_all("test").count(), 1);
+}
+
+#[test]
+fn concurrent_readers_and_writers() {
+    let db = Arc::new(shared());
+
+    std::thread::scope(|scope| {
+        for thread in 0..4 {
+            let db = db.clone();
+            scope.spawn(move || {
+                for i in 0..25 {
+                    db.write(|file| {
+                        file.insert(
+                            "test",
+                            vec![document! {"_id" => thread * 100 + i}],
+                            BsonAutoId::ObjectId,
+                        )
+                        .unwrap()
+                    });
+                }
+            });
+        }
+
+        for _ in 0..4 {
+            let db = db.clone();
+            scope.spawn(move || {
+                let mut last = 0;
+                for _ in 0..25 {
+                    let snapshot = db.read();
+                    let count = snapshot.get_all("test").count();
+                    // writes are published in order
+                    assert!(count >= last);
+                    assert!(snapshot.check_integrity().is_empty());
+                    last = count;
+                }
+            });
+        }
+    });
+
+    let file = Arc::into_inner(db).unwrap().into_inner();
+    assert_eq!(file.get_all("test").count(), 100);
+    assert!(file.check_integrity().is_empty());
+}
+
+#[tokio::test]
+async fn write_async() {
+    let db = shared();
+
+    let inserted = db
+        .write_async(|file| file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId))
+        .await
+        .unwrap();
+
+    assert_eq!(inserted, 1);
+    assert_eq!(db.read().get_all("test").count(), 1);
+}
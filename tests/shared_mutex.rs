@@ -0,0 +1,31 @@
+#![cfg(all(feature = "shared-mutex", target_os = "linux"))]
+
+use std::time::Duration;
+use vrc_get_litedb::shared_mutex::SharedMutex;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn excludes_other_instance() {
+    let name = format!("Global\\litedb-rs-test-{}", std::process::id());
+    let first = SharedMutex::new(&name).await.unwrap();
+    let second = SharedMutex::new(&name).await.unwrap();
+
+    let guard = first.lock().await.unwrap();
+    assert!(
+        tokio::time::timeout(Duration::from_millis(100), second.lock())
+            .await
+            .is_err(),
+        "the lock should not be acquired while other instance holds it"
+    );
+    drop(guard);
+
+    drop(second.lock().await.unwrap());
+
+    drop(first);
+    drop(second);
+    assert!(
+        !std::env::temp_dir()
+            .join(".dotnet/shm/global")
+            .join(&name["Global\\".len()..])
+            .exists()
+    );
+}
@@ -0,0 +1,191 @@
+use vrc_get_litedb::ErrorKind;
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+const MAX_DOCUMENT_SIZE: usize = 2047 * (8192 - 32 - 4 - 6);
+const MAX_INDEX_KEY_LENGTH: usize = 1023;
+
+fn test_file() -> LiteDBFile {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "key" => "a"}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        false,
+    )
+    .unwrap();
+    file
+}
+
+fn large_binary() -> bson::Binary {
+    bson::Binary::new(vec![0; MAX_DOCUMENT_SIZE])
+}
+
+#[test]
+fn document_too_large() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    let error = file
+        .insert(
+            "test",
+            vec![document! {"_id" => 2, "data" => large_binary()}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::DocumentSizeExceeded);
+    assert_eq!(error.document_id(), Some(&bson::Value::Int32(2)));
+    assert_eq!(error.size_limit(), Some(MAX_DOCUMENT_SIZE));
+
+    let error = file
+        .update(
+            "test",
+            vec![document! {"_id" => 1, "data" => large_binary()}],
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::DocumentSizeExceeded);
+    assert_eq!(error.document_id(), Some(&bson::Value::Int32(1)));
+
+    assert_eq!(file.serialize(), original);
+}
+
+#[test]
+fn index_key_too_long() {
+    let mut file = test_file();
+    let long_key = "a".repeat(MAX_INDEX_KEY_LENGTH);
+
+    let error = file
+        .insert(
+            "test",
+            vec![document! {"_id" => 2, "key" => long_key.as_str()}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::IndexKeySizeExceeded);
+    assert_eq!(error.index_name(), Some("key"));
+    assert_eq!(error.document_id(), Some(&bson::Value::Int32(2)));
+    assert_eq!(error.size_limit(), Some(MAX_INDEX_KEY_LENGTH));
+
+    let error = file
+        .update(
+            "test",
+            vec![document! {"_id" => 1, "key" => long_key.as_str()}],
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::IndexKeySizeExceeded);
+    assert_eq!(error.document_id(), Some(&bson::Value::Int32(1)));
+
+    let documents = file.get_all("test").cloned().collect::<Vec<_>>();
+    assert_eq!(documents, vec![document! {"_id" => 1, "key" => "a"}]);
+    assert!(file.check_integrity().is_empty());
+}
+
+#[test]
+fn index_key_too_long_on_ensure_index() {
+    let mut file = test_file();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 2, "other" => "a".repeat(MAX_INDEX_KEY_LENGTH)}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+    let original = file.serialize();
+
+    let error = file
+        .ensure_index(
+            "test",
+            "other",
+            BsonExpression::create("$.other").unwrap(),
+            false,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::IndexKeySizeExceeded);
+    assert_eq!(error.index_name(), Some("other"));
+    assert_eq!(error.document_id(), Some(&bson::Value::Int32(2)));
+
+    // the partially built index is removed
+    assert_eq!(file.indexes("test").len(), 2);
+    assert_eq!(file.serialize(), original);
+    assert!(file.check_integrity().is_empty());
+}
+
+#[test]
+fn no_space_for_index() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    let long_expression = format!("$.key = '{}'", "a".repeat(8100));
+    let error = file
+        .ensure_index(
+            "test",
+            "long",
+            BsonExpression::create(&long_expression).unwrap(),
+            false,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidIndex);
+
+    let error = file
+        .ensure_index(
+            "new_collection",
+            "long",
+            BsonExpression::create(&long_expression).unwrap(),
+            false,
+        )
+        .unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidIndex);
+
+    assert_eq!(file.serialize(), original);
+}
+
+#[test]
+fn too_many_indexes() {
+    let mut file = test_file();
+
+    let mut created = 0;
+    let error = loop {
+        let name = format!("i{created}");
+        match file.ensure_index(
+            "test",
+            &name,
+            BsonExpression::create(&format!("$.{name}")).unwrap(),
+            false,
+        ) {
+            Ok(_) => created += 1,
+            Err(e) => break e,
+        }
+    };
+    assert_eq!(error.kind(), ErrorKind::InvalidIndex);
+    // `_id` and `key` indexes
+    assert_eq!(created + 2, u8::MAX as usize);
+
+    // the file with full indexes can be written and read back
+    let parsed = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(parsed.indexes("test").len(), u8::MAX as usize);
+}
+
+#[test]
+fn largest_document() {
+    let mut file = LiteDBFile::new();
+
+    // 4 bytes document length, "_id" field, "data" field header, and terminator
+    let header = 4 + (1 + 4 + 4) + (1 + 5 + 4 + 1) + 1;
+    let data = bson::Binary::new(vec![0; MAX_DOCUMENT_SIZE - header]);
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1, "data" => data}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    let parsed = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(parsed.get_all("test").count(), 1);
+}
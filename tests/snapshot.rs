@@ -0,0 +1,65 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+fn documents(file: &LiteDBFile, collection: &str) -> Vec<bson::Document> {
+    file.get_all(collection).cloned().collect()
+}
+
+#[test]
+fn snapshot_is_independent() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let projects = documents(&file, "projects");
+
+    let snapshot = file.snapshot();
+    file.drop_collection("projects");
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+
+    assert_eq!(documents(&snapshot, "projects"), projects);
+    assert_eq!(snapshot.get_all("test").count(), 0);
+    assert!(snapshot.check_integrity().is_empty());
+
+    let serialized = LiteDBFile::parse(&snapshot.serialize()).unwrap();
+    assert_eq!(documents(&serialized, "projects"), projects);
+
+    // modifying the snapshot does not affect the original
+    let mut snapshot = snapshot;
+    snapshot.drop_collection("unityVersions");
+    assert!(file.get_all("unityVersions").count() > 0);
+    assert!(file.check_integrity().is_empty());
+}
+
+#[test]
+fn restore() {
+    let original = include_bytes!("vcc.liteDb");
+    let mut file = LiteDBFile::parse(original).unwrap();
+    let projects = documents(&file, "projects");
+    let versions = documents(&file, "unityVersions");
+
+    let snapshot = file.snapshot();
+    file.drop_collection("projects");
+    file.restore(snapshot);
+
+    assert_eq!(documents(&file, "projects"), projects);
+    assert!(file.check_integrity().is_empty());
+    // nothing changed from the loaded file
+    assert!(file.serialize_log().unwrap().is_empty());
+
+    // restore after the file is written
+    let snapshot = file.snapshot();
+    file.drop_collection("projects");
+    file.insert("test", vec![document! {"_id" => 1}], BsonAutoId::ObjectId)
+        .unwrap();
+    let mut log = file.serialize_log().unwrap();
+
+    file.restore(snapshot);
+    assert!(file.check_integrity().is_empty());
+    log.extend(file.serialize_log().unwrap());
+
+    let file = LiteDBFile::parse_with_log(original, &log).unwrap();
+    assert_eq!(documents(&file, "projects"), projects);
+    assert_eq!(documents(&file, "unityVersions"), versions);
+    assert_eq!(file.get_all("test").count(), 0);
+    assert!(LiteDBFile::check_file_integrity(&file.serialize()).is_empty());
+}
@@ -0,0 +1,68 @@
+use vrc_get_litedb::document;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+const PAGE_SIZE: usize = 8192;
+
+#[test]
+fn parsed_file() {
+    let data = include_bytes!("vcc.liteDb");
+    let file = LiteDBFile::parse(data).unwrap();
+    let stats = file.stats();
+
+    let pages = stats.pages.unwrap();
+    assert_eq!(pages.total_pages, data.len() / PAGE_SIZE);
+    assert_eq!(pages.free_bytes.len(), pages.total_pages);
+    // all pages except the header page
+    assert_eq!(
+        pages.collection_pages + pages.data_pages + pages.index_pages + pages.empty_pages,
+        pages.total_pages - 1
+    );
+    assert_eq!(pages.collection_pages, stats.collections.len());
+
+    for collection in &stats.collections {
+        assert_eq!(collection.documents, file.get_all(&collection.name).count());
+        assert!(collection.document_bytes > 0);
+        assert!(collection.data_pages.unwrap() > 0);
+        assert!(collection.index_pages.unwrap() > 0);
+
+        for index in &collection.indexes {
+            assert_eq!(index.nodes, collection.documents);
+            assert!(index.average_levels >= 1.0);
+        }
+    }
+
+    assert_eq!(
+        stats
+            .collections
+            .iter()
+            .map(|x| x.data_pages.unwrap())
+            .sum::<usize>(),
+        pages.data_pages
+    );
+}
+
+#[test]
+fn in_memory_file() {
+    let mut file = LiteDBFile::new();
+    file.insert(
+        "test",
+        vec![document! {"_id" => 1}, document! {"_id" => 2}],
+        BsonAutoId::ObjectId,
+    )
+    .unwrap();
+
+    let stats = file.stats();
+    assert!(stats.pages.is_none());
+
+    let collection = &stats.collections[0];
+    assert_eq!(collection.name, "test");
+    assert_eq!(collection.documents, 2);
+    // {"_id": 1} is 14 bytes in BSON
+    assert_eq!(collection.document_bytes, 28);
+    assert_eq!(collection.data_pages, None);
+    assert_eq!(collection.indexes[0].name, "_id");
+    assert_eq!(collection.indexes[0].nodes, 2);
+
+    let empty = LiteDBFile::new().stats();
+    assert!(empty.collections.is_empty());
+}
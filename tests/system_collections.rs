@@ -0,0 +1,148 @@
+use vrc_get_litedb::bson;
+use vrc_get_litedb::file_io::{Collation, CompareOptions, LiteDBFile};
+
+const PAGE_SIZE: usize = 8192;
+
+fn documents(file: &LiteDBFile, collection: &str) -> Vec<bson::Document> {
+    file.system_collection(collection).unwrap()
+}
+
+#[test]
+fn cols() {
+    let file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let cols = documents(&file, "$cols");
+
+    let user = cols
+        .iter()
+        .filter(|x| x.get("type").as_str() == Some("user"))
+        .map(|x| x.get("name").as_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(user, file.get_collection_names());
+
+    let system = cols
+        .iter()
+        .filter(|x| x.get("type").as_str() == Some("system"))
+        .map(|x| x.get("name").as_str().unwrap())
+        .collect::<Vec<_>>();
+    for name in ["$cols", "$database", "$dump", "$indexes", "$page_list"] {
+        assert!(system.contains(&name), "{name} is missing");
+    }
+
+    // system collections are case-insensitive like user collections
+    assert_eq!(documents(&file, "$COLS"), cols);
+}
+
+#[test]
+fn indexes() {
+    let file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let indexes = documents(&file, "$indexes");
+
+    let expected = file
+        .get_collection_names()
+        .iter()
+        .flat_map(|x| file.indexes(x))
+        .collect::<Vec<_>>();
+    assert_eq!(indexes.len(), expected.len());
+
+    for (document, index) in indexes.iter().zip(&expected) {
+        assert_eq!(
+            document.get("collection").as_str(),
+            Some(&*index.collection)
+        );
+        assert_eq!(document.get("slot"), &bson::Value::Int32(index.slot as i32));
+        assert_eq!(document.get("idxType"), &bson::Value::Int32(0));
+        assert_eq!(document.get("name").as_str(), Some(&*index.name));
+        assert_eq!(
+            document.get("expression").as_str(),
+            Some(&*index.expression)
+        );
+        assert_eq!(document.get("unique"), &bson::Value::Boolean(index.unique));
+        assert_eq!(
+            document.get("maxLevel"),
+            &bson::Value::Int32(index.max_level as i32)
+        );
+    }
+}
+
+#[test]
+fn database() {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.set_user_version(5);
+    file.set_collation(Collation::new(127, CompareOptions::ORDINAL))
+        .unwrap();
+
+    let database = documents(&file, "$database");
+    assert_eq!(database.len(), 1);
+    let database = &database[0];
+
+    let size = file.serialize().len();
+    assert_eq!(database.get("name"), &bson::Value::Null);
+    assert_eq!(database.get("encrypted"), &bson::Value::Boolean(false));
+    assert_eq!(database.get("readOnly"), &bson::Value::Boolean(false));
+    assert_eq!(
+        database.get("dataFileSize"),
+        &bson::Value::Int32(size as i32)
+    );
+    assert_eq!(
+        database.get("lastPageID"),
+        &bson::Value::Int32((size / PAGE_SIZE - 1) as i32)
+    );
+    assert_eq!(
+        database.get("creationTime"),
+        &bson::Value::DateTime(file.creation_time())
+    );
+
+    let pragmas = database.get("pragmas").as_document().unwrap();
+    assert_eq!(pragmas.get("USER_VERSION"), &bson::Value::Int32(5));
+    assert_eq!(pragmas.get("COLLATION").as_str(), Some("/Ordinal"));
+    assert_eq!(
+        pragmas.get("TIMEOUT"),
+        &bson::Value::Int32(file.timeout_seconds())
+    );
+    assert_eq!(pragmas.get("UTC_DATE"), &bson::Value::Boolean(false));
+}
+
+#[test]
+fn page_list_and_dump() {
+    let file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    let data = file.serialize();
+
+    let page_list = documents(&file, "$page_list");
+    let dump = documents(&file, "$dump");
+    assert_eq!(page_list.len(), data.len() / PAGE_SIZE);
+    assert_eq!(dump.len(), page_list.len());
+
+    assert_eq!(page_list[0].get("pageType").as_str(), Some("Header"));
+    assert_eq!(page_list[0].get("collection").as_str(), Some("-"));
+
+    for (index, (page, dump)) in page_list.iter().zip(&dump).enumerate() {
+        assert_eq!(page.get("pageID"), &bson::Value::Int32(index as i32));
+        assert_eq!(dump.get("pageID"), page.get("pageID"));
+        assert_eq!(dump.get("pageType"), page.get("pageType"));
+        assert_eq!(dump.get("collection"), page.get("collection"));
+        assert_eq!(dump.get("freeBytes"), page.get("freeBytes"));
+        assert_eq!(
+            dump.get("buffer").as_binary().unwrap().bytes(),
+            &data[index * PAGE_SIZE..][..PAGE_SIZE]
+        );
+    }
+
+    // pages of each collection are listed with the name
+    for name in file.get_collection_names() {
+        let pages = page_list
+            .iter()
+            .filter(|x| x.get("collection").as_str() == Some(&*name))
+            .map(|x| x.get("pageType").as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert!(pages.contains(&"Collection"), "{name}: {pages:?}");
+        assert!(pages.contains(&"Data"), "{name}: {pages:?}");
+        assert!(pages.contains(&"Index"), "{name}: {pages:?}");
+    }
+}
+
+#[test]
+fn user_collection() {
+    let file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    assert!(file.system_collection("projects").is_none());
+    assert_eq!(file.get_all("$cols").count(), 0);
+}
@@ -0,0 +1,115 @@
+use vrc_get_litedb::document;
+use vrc_get_litedb::expression::BsonExpression;
+use vrc_get_litedb::file_io::{BsonAutoId, LiteDBFile};
+
+fn test_file() -> LiteDBFile {
+    let mut file = LiteDBFile::parse(include_bytes!("vcc.liteDb")).unwrap();
+    file.ensure_index(
+        "test",
+        "key",
+        BsonExpression::create("$.key").unwrap(),
+        true,
+    )
+    .unwrap();
+    file
+}
+
+#[test]
+fn rollback_on_error() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    {
+        let mut transaction = file.begin_transaction();
+        transaction.drop_collection("projects");
+        transaction.set_user_version(10);
+        let result = transaction.insert(
+            "test",
+            vec![
+                document! {"_id" => 1, "key" => "a"},
+                document! {"_id" => 2, "key" => "b"},
+                document! {"_id" => 3, "key" => "a"},
+            ],
+            BsonAutoId::ObjectId,
+        );
+        assert!(result.is_err());
+        assert_eq!(transaction.get_all("test").count(), 2);
+        // dropped without commit
+    }
+
+    assert_eq!(file.get_all("test").count(), 0);
+    assert_eq!(file.user_version(), 0);
+    assert!(file.check_integrity().is_empty());
+    assert_eq!(file.serialize(), original);
+
+    let transaction = file.begin_transaction();
+    drop(transaction);
+    assert_eq!(file.serialize(), original);
+}
+
+#[test]
+fn rollback() {
+    let mut file = test_file();
+    let original = file.serialize();
+
+    let mut transaction = file.begin_transaction();
+    transaction
+        .insert(
+            "test",
+            vec![document! {"_id" => 1, "key" => "a"}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap();
+    transaction.delete("projects", &[1.into()]);
+    transaction.rollback();
+
+    assert_eq!(file.serialize(), original);
+}
+
+#[test]
+fn commit() {
+    let mut file = test_file();
+
+    let mut transaction = file.begin_transaction();
+    transaction
+        .insert(
+            "test",
+            vec![document! {"_id" => 1, "key" => "a"}],
+            BsonAutoId::ObjectId,
+        )
+        .unwrap();
+    transaction.drop_collection("projects");
+    transaction.commit();
+
+    assert_eq!(file.get_all("test").count(), 1);
+    assert!(
+        !file
+            .get_collection_names()
+            .contains(&"projects".to_string())
+    );
+    assert!(file.check_integrity().is_empty());
+
+    let file = LiteDBFile::parse(&file.serialize()).unwrap();
+    assert_eq!(file.get_all("test").count(), 1);
+}
+
+#[test]
+fn rollback_restores_random() {
+    let mut file = test_file();
+    file.set_random_seed(42);
+    let mut expected = file.clone();
+
+    let mut transaction = file.begin_transaction();
+    transaction
+        .insert("test", vec![document! {"key" => "a"}], BsonAutoId::ObjectId)
+        .unwrap();
+    transaction.rollback();
+
+    // auto ids after rollback are the same as if the transaction never happened
+    file.insert("test", vec![document! {"key" => "b"}], BsonAutoId::ObjectId)
+        .unwrap();
+    expected
+        .insert("test", vec![document! {"key" => "b"}], BsonAutoId::ObjectId)
+        .unwrap();
+    assert_eq!(file.serialize(), expected.serialize());
+}